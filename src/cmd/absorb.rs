@@ -0,0 +1,313 @@
+use std::{collections::HashSet, error::Error, path::PathBuf};
+
+use clap::Parser;
+use git2::{Blame, Delta, FileMode, Patch};
+use gix::ObjectId;
+
+use crate::{
+    cmd::{commit::SignOpts, common},
+    git::{rewrite, CommitFilter, DiffOpts, Repo, SignMode},
+    guard::unpushed,
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Fold staged hunks into the unpushed commits that last touched their lines")]
+pub struct Opts {
+    #[clap(long, help = "Only show where the hunks would go")]
+    dry_run: bool,
+
+    #[clap(flatten)]
+    sign: SignOpts,
+}
+
+// A hunk of the staged diff without context, `added` holds the content of its new lines
+#[derive(Debug)]
+struct Hunk {
+    old_start: usize,
+    old_lines: usize,
+    added: Vec<u8>,
+}
+
+// The commit that last changed all lines of the hunk, insertions use the lines around them
+fn find_target(blame: &Blame<'_>, old_start: usize, old_lines: usize) -> Option<git2::Oid> {
+    let (start, end) = match old_lines {
+        0 => (old_start.max(1), old_start + 1),
+        _ => (old_start, old_start + old_lines - 1),
+    };
+    let commits = (start..=end)
+        .filter_map(|line| blame.get_line(line))
+        .map(|hunk| hunk.final_commit_id())
+        .collect::<HashSet<_>>();
+
+    match commits.len() {
+        1 => commits.into_iter().next(),
+        _ => None,
+    }
+}
+
+// Applies some of the hunks diffed against `content`, they are placed by their original line
+// numbers so the hunks that are left out don't shift the others
+fn apply(content: &[u8], hunks: &[&Hunk]) -> Vec<u8> {
+    let lines = content.split_inclusive(|&b| b == b'\n').collect::<Vec<_>>();
+    let mut hunks = hunks.to_vec();
+    let mut result = vec![];
+    let mut pos = 0;
+
+    hunks.sort_by_key(|hunk| hunk.old_start);
+
+    for hunk in hunks {
+        // Insertions go after `old_start`, other hunks replace the lines starting at it
+        let start = match hunk.old_lines {
+            0 => hunk.old_start,
+            _ => hunk.old_start - 1,
+        }
+        .clamp(pos, lines.len());
+
+        result.extend(lines[pos..start].concat());
+        result.extend_from_slice(&hunk.added);
+        pos = (start + hunk.old_lines).min(lines.len());
+    }
+
+    result.extend(lines[pos..].concat());
+    result
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{count} {noun}{}", if count == 1 { "" } else { "s" })
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+    let head = repo.find_commit(repo.head()?.target()?)?;
+    let head_tree = head.find_tree()?;
+    let diff = repo.diff(
+        DiffOpts::default()
+            .with_staged(&head_tree)
+            .exact()
+            .without_context(),
+    )?;
+    let unpushed = unpushed(&repo)?;
+    let absorbable = |oid: &git2::Oid| match &unpushed {
+        Some(unpushed) => unpushed.contains(oid),
+        None => true,
+    };
+
+    let mut files: Vec<(PathBuf, FileMode, Vec<(git2::Oid, Hunk)>)> = vec![];
+    let mut skipped = 0;
+
+    for i in 0..diff.deltas().len() {
+        let Some(patch) = Patch::from_diff(&diff, i)? else {
+            continue;
+        };
+        let delta = patch.delta();
+        let (path, mode) = (delta.new_file().path(), delta.new_file().mode());
+        // Only changes to files that exist in HEAD under the same name can be blamed
+        let blame = match (delta.status(), path) {
+            (Delta::Modified, Some(path)) => repo.blame(path, None, None).ok(),
+            _ => None,
+        };
+        let mut hunks = vec![];
+
+        for h in 0..patch.num_hunks() {
+            let (hunk, lines) = patch.hunk(h)?;
+            let (old_start, old_lines) = (hunk.old_start() as usize, hunk.old_lines() as usize);
+            let target = blame
+                .as_ref()
+                .and_then(|blame| find_target(blame, old_start, old_lines))
+                .filter(absorbable);
+            let Some(target) = target else {
+                skipped += 1;
+                continue;
+            };
+            let mut added = vec![];
+
+            for l in 0..lines {
+                let line = patch.line_in_hunk(h, l)?;
+
+                if line.origin() == '+' {
+                    added.extend_from_slice(line.content());
+                }
+            }
+
+            hunks.push((
+                target,
+                Hunk {
+                    old_start,
+                    old_lines,
+                    added,
+                },
+            ));
+        }
+
+        if let Some(path) = path.filter(|_| !hunks.is_empty()) {
+            files.push((path.to_path_buf(), mode, hunks));
+        }
+    }
+
+    // Targets are ordered oldest first, a target that isn't on the first-parent history can't be
+    // rewritten and its hunks stay staged
+    let ids = files
+        .iter()
+        .flat_map(|(_, _, hunks)| hunks.iter().map(|(target, _)| *target))
+        .collect::<HashSet<_>>();
+    let filter = CommitFilter {
+        first_parent: true,
+        ..Default::default()
+    };
+    let mut targets = vec![];
+
+    for commit in repo.commits(&repo.head()?, filter)? {
+        let commit = commit?;
+
+        if ids.contains(&commit.id()) {
+            targets.push(commit);
+
+            if targets.len() == ids.len() {
+                break;
+            }
+        }
+    }
+
+    targets.reverse();
+
+    let count = |target: git2::Oid| {
+        files
+            .iter()
+            .flat_map(|(_, _, hunks)| hunks.iter())
+            .filter(|(t, _)| *t == target)
+            .count()
+    };
+
+    skipped += ids
+        .iter()
+        .filter(|id| !targets.iter().any(|target| target.id() == **id))
+        .map(|id| count(*id))
+        .sum::<usize>();
+
+    if targets.is_empty() {
+        return Err("No staged hunks can be absorbed".into());
+    }
+
+    for target in &targets {
+        ui.renderln(&block!(
+            Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+                target.id().as_bytes()
+            )?)),
+            spacer!(),
+            Node::text_fill(target.title()),
+            right_aligned!(dimmed!(text!(plural(count(target.id()), "hunk"))))
+        ))?;
+    }
+
+    if skipped > 0 {
+        ui.renderln(&continued!(dimmed!(text!(format!(
+            "{} left staged",
+            plural(skipped, "hunk")
+        )))))?;
+    }
+
+    if opts.dry_run {
+        return Ok(());
+    }
+
+    // Fixups are chained on top of HEAD, one per target, each file holds the hunks of the
+    // targets so far applied to its content in HEAD
+    let config = repo.config()?;
+    let signature = config.user.signature()?;
+    let mut commits = repo.commits_since(&targets[0])?;
+    let mut parent = repo.find_commit(head.id())?;
+
+    for (i, target) in targets.iter().enumerate() {
+        let included = targets[..=i]
+            .iter()
+            .map(|target| target.id())
+            .collect::<HashSet<_>>();
+        let updated = files
+            .iter()
+            .filter(|(_, _, hunks)| hunks.iter().any(|(t, _)| *t == target.id()))
+            .map(|(path, mode, hunks)| {
+                let hunks = hunks
+                    .iter()
+                    .filter(|(t, _)| included.contains(t))
+                    .map(|(_, hunk)| hunk)
+                    .collect::<Vec<_>>();
+
+                Ok((
+                    path.as_path(),
+                    *mode,
+                    apply(&repo.read_file(&head, path)?, &hunks),
+                ))
+            })
+            .collect::<Result<Vec<_>, git2::Error>>()?;
+        let tree = repo.update_files(&parent.find_tree()?, &updated)?;
+        let fixup = repo.write_commit(
+            &config,
+            &signature,
+            &signature,
+            &format!("fixup! {}", target.title()),
+            &tree,
+            &[&parent.0],
+            SignMode::Never,
+        )?;
+
+        parent = repo.find_commit(fixup)?;
+        commits.push(repo.find_commit(fixup)?);
+    }
+
+    common::backup(&repo, "absorb")?;
+
+    let onto = targets[0].parent()?.map(|parent| parent.id());
+    let new_head = repo
+        .replay(onto, &rewrite::autosquash(commits), opts.sign.mode())?
+        .ok_or("Nothing to rewrite")?;
+
+    // The index is left alone so the hunks that weren't absorbed stay staged
+    repo.head()?.set_target(new_head, "absorb")?;
+
+    ui.renderln(&message_with_icon(Icon::Check, "Absorbed staged changes"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(old_start: usize, old_lines: usize, added: &str) -> Hunk {
+        Hunk {
+            old_start,
+            old_lines,
+            added: added.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_apply_keeps_offsets_of_skipped_hunks() {
+        let content = b"a\nb\nc\nd\ne\n";
+        // The first and last hunks belong to two targets, the insertion stays staged
+        let first = hunk(1, 1, "A\nA2\n");
+        let staged = hunk(2, 0, "x\ny\n");
+        let last = hunk(4, 2, "D\n");
+
+        assert_eq!(apply(content, &[&first]), b"A\nA2\nb\nc\nd\ne\n");
+        assert_eq!(apply(content, &[&last, &first]), b"A\nA2\nb\nc\nD\n");
+        assert_eq!(
+            apply(content, &[&first, &staged, &last]),
+            b"A\nA2\nb\nx\ny\nc\nD\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_insertions_and_deletions() {
+        let content = b"a\nb\nc\n";
+
+        assert_eq!(apply(content, &[&hunk(0, 0, "z\n")]), b"z\na\nb\nc\n");
+        assert_eq!(apply(content, &[&hunk(3, 0, "d\n")]), b"a\nb\nc\nd\n");
+        assert_eq!(apply(content, &[&hunk(2, 1, "")]), b"a\nc\n");
+        assert_eq!(apply(content, &[]), content);
+    }
+}
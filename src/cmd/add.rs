@@ -6,7 +6,7 @@ use crate::{
     git::Repo,
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
         select,
     },
 };
@@ -28,7 +28,7 @@ fn file_added(path: &Path) -> Node {
 }
 
 pub fn add_callback(path: &Path) {
-    let _ = TermRenderer::default().renderln(&file_added(path));
+    let _ = Renderer::default().renderln(&file_added(path));
 }
 
 pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
@@ -5,12 +5,13 @@ use gix::ObjectId;
 use inquire::ui::{Color, RenderConfig};
 
 use crate::{
-    cmd::add::add_callback,
+    cmd::{add::add_callback, commit::SignOpts, common},
     git::Repo,
+    guard::{self, ForceOpts},
     term::{
         self,
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
     },
 };
 
@@ -23,11 +24,20 @@ pub struct Opts {
     #[clap(short, long, help = "Amend without prompting")]
     yes: bool,
 
+    #[clap(flatten)]
+    sign: SignOpts,
+
+    #[clap(flatten)]
+    force: ForceOpts,
+
     #[clap(help = "Commit message")]
     message: Option<String>,
 }
 
 pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    // Checked before anything is staged so that a refused amend leaves the index alone
+    guard::check_rewrite(&repo, repo.head()?.target()?, &opts.force)?;
+
     let mut index = repo.index()?;
 
     if opts.add_all {
@@ -35,7 +45,7 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
         index.write()?;
     }
 
-    let mut ui = TermRenderer::default();
+    let mut ui = Renderer::default();
     let oid = index.write_tree()?;
     let mut head = repo.head()?;
     let tree = repo.find_tree(oid)?;
@@ -62,11 +72,12 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
             Some(message) => message,
             None => commit.message()?.to_string(),
         };
-        let oid = repo.create_commit(&tree, &message, Some(&parent))?;
+        let oid = repo.create_commit(&tree, &message, Some(&parent), opts.sign.mode())?;
 
         (oid, message)
     };
 
+    common::backup(&repo, "amend")?;
     head.set_target(oid, &format!("commit amended: {message}"))?;
 
     ui.renderln(&continued!(block!(
@@ -0,0 +1,244 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, ValueHint};
+use gix::ObjectId;
+use minus::Pager;
+use resolve_path::PathResolveExt;
+
+use crate::{
+    git::{self, Repo},
+    term::{
+        self, layout,
+        node::prelude::*,
+        render::{self, Format, Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Show which commit last changed each line of a file")]
+pub struct Opts {
+    #[clap(value_hint = ValueHint::FilePath, help = "File to blame")]
+    path: PathBuf,
+
+    #[clap(
+        short = 'L',
+        value_parser = parse_range,
+        help = "Only blame the lines in the range (e.g. 10,20 or 10,+5)"
+    )]
+    range: Option<(usize, usize)>,
+
+    #[clap(long, help = "Blame the file as it was in this revision")]
+    rev: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "LINE",
+        help = "Blame from the parent of the commit that last changed this line"
+    )]
+    parent_of: Option<usize>,
+
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "Ignore the revisions listed in the file (defaults to blame.ignoreRevsFile)"
+    )]
+    ignore_revs_file: Option<PathBuf>,
+
+    #[clap(long, help = "Disable the pager")]
+    no_pager: bool,
+}
+
+fn parse_range(input: &str) -> Result<(usize, usize), String> {
+    let (start, end) = input
+        .split_once(',')
+        .ok_or_else(|| format!("expected <start>,<end> but got: {input}"))?;
+    let start = start.parse::<usize>().map_err(|e| e.to_string())?;
+
+    if start == 0 {
+        return Err(format!("invalid line range: {input}"));
+    }
+
+    let end = match end.strip_prefix('+') {
+        Some(count) => start + count.parse::<usize>().map_err(|e| e.to_string())? - 1,
+        None => end.parse::<usize>().map_err(|e| e.to_string())?,
+    };
+
+    if end < start {
+        return Err(format!("invalid line range: {input}"));
+    }
+
+    Ok((start, end))
+}
+
+fn ignored_revs(path: &Path) -> Result<HashSet<git2::Oid>, Box<dyn Error>> {
+    let mut revs = HashSet::new();
+
+    for line in fs::read_to_string(path)?.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+
+        if !line.is_empty() {
+            revs.insert(git2::Oid::from_str(line)?);
+        }
+    }
+
+    Ok(revs)
+}
+
+struct Line {
+    commit: git2::Oid,
+    author: String,
+    time: String,
+}
+
+fn attribute(
+    repo: &Repo,
+    hunk: &git2::BlameHunk<'_>,
+    line: usize,
+    ignored: &HashSet<git2::Oid>,
+) -> Result<Line, Box<dyn Error>> {
+    let commit = hunk.final_commit_id();
+
+    // Ignored commits pass the line on to the previous commit that touched it, this is an
+    // approximation as the line number might have changed in the parent
+    if ignored.contains(&commit) {
+        let parent = repo.find_commit(commit)?.parent()?;
+        let orig_line = hunk.orig_start_line() + line - hunk.final_start_line();
+
+        if let (Some(parent), Some(path)) = (parent, hunk.orig_path()) {
+            if let Ok(blame) = repo.blame(path, Some(parent.id()), Some((orig_line, orig_line))) {
+                if let Some(hunk) = blame.get_line(orig_line) {
+                    return attribute(repo, &hunk, orig_line, ignored);
+                }
+            }
+        }
+    }
+
+    let signature = hunk.final_signature();
+
+    Ok(Line {
+        commit,
+        author: String::from_utf8_lossy(signature.name_bytes()).to_string(),
+        time: term::relative_time(git::parse_local_time(signature.when())),
+    })
+}
+
+fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{text}{}",
+        " ".repeat(width.saturating_sub(layout::display_width(text)))
+    )
+}
+
+fn render(mut ui: impl Render, repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let path = repo.relative_path(&opts.path);
+    let rev = opts.rev.as_deref().unwrap_or("HEAD");
+    let mut commit = repo.resolve_commit(rev)?.ok_or("Revision not found")?;
+
+    if let Some(line) = opts.parent_of {
+        let blame = repo.blame(&path, Some(commit.id()), Some((line, line)))?;
+        let hunk = blame.get_line(line).ok_or("Line not found")?;
+
+        commit = repo
+            .find_commit(hunk.final_commit_id())?
+            .parent()?
+            .ok_or("The line was added in the root commit")?;
+    }
+
+    // The option is relative to the current directory, the config to the root of the worktree
+    let ignore_revs_file = match opts.ignore_revs_file {
+        Some(path) => Some(path.resolve().into_owned()),
+        None => repo.config()?.blame.ignore_revs_file.map(|path| {
+            let root = repo.workdir().unwrap_or_else(|| repo.path());
+            Path::new(&path).resolve_in(root).into_owned()
+        }),
+    };
+    let ignored = match ignore_revs_file {
+        Some(path) => ignored_revs(&path)?,
+        None => HashSet::new(),
+    };
+
+    let content = repo.read_file(&commit, &path)?;
+    let content = String::from_utf8_lossy(&content);
+    let blame = repo.blame(&path, Some(commit.id()), opts.range)?;
+    let (start, end) = opts.range.unwrap_or((1, content.lines().count()));
+
+    let mut lines = vec![];
+
+    for (i, text) in content
+        .lines()
+        .enumerate()
+        .skip(start - 1)
+        .take(end + 1 - start)
+    {
+        let Some(hunk) = blame.get_line(i + 1) else {
+            continue;
+        };
+
+        lines.push((i + 1, text, attribute(&repo, &hunk, i + 1, &ignored)?));
+    }
+
+    let author_width = lines
+        .iter()
+        .map(|(_, _, line)| layout::display_width(&line.author))
+        .max()
+        .unwrap_or_default();
+    let time_width = lines
+        .iter()
+        .map(|(_, _, line)| line.time.len())
+        .max()
+        .unwrap_or_default();
+    let number_width = end.to_string().len();
+
+    for (number, text, line) in lines {
+        ui.renderln(&block!(
+            Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+                line.commit.as_bytes()
+            )?)),
+            spacer!(),
+            text!(pad(&line.author, author_width)),
+            spacer!(),
+            dimmed!(text!(pad(&line.time, time_width))),
+            spacer!(),
+            dimmed!(text!(format!("{number:>number_width$}"))),
+            spacer!(),
+            text!(text.to_string())
+        ))?;
+    }
+
+    Ok(())
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    if opts.no_pager || render::format() == Format::Json {
+        render(Renderer::default(), repo, opts)
+    } else {
+        render::enable_pager_color();
+
+        let mut pager = Pager::new();
+        pager.set_prompt(format!("blame {}, q to quit", opts.path.display()))?;
+
+        render(Renderer::new(&mut pager), repo, opts)?;
+        minus::page_all(pager)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("10,20"), Ok((10, 20)));
+        assert_eq!(parse_range("10,+5"), Ok((10, 14)));
+        assert!(parse_range("20,10").is_err());
+        assert!(parse_range("0,10").is_err());
+        assert!(parse_range("10").is_err());
+    }
+}
@@ -3,10 +3,11 @@ use std::error::Error;
 use clap::Parser;
 
 use crate::{
-    git::{Branch, CheckoutError, Optional, Ref, RemoteOpts, Repo},
+    cmd::common,
+    git::{Branch, CheckoutError, Optional, Ref, RemoteOpts, Repo, SignMode},
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
         select,
     },
 };
@@ -92,9 +93,13 @@ pub fn run(mut repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
     };
 
     if !try_checkout(&repo, &branch.into())? {
-        repo.save_stash(&format!("auto stash before checkout to: {branch_name}"))?;
+        common::backup(&repo, &format!("checkout {branch_name}"))?;
+        repo.save_stash(
+            &format!("auto stash before checkout to: {branch_name}"),
+            SignMode::Config,
+        )?;
 
-        let mut ui = TermRenderer::default();
+        let mut ui = Renderer::default();
         ui.renderln(&message_with_icon(Icon::Check, "Changes stashed"))?;
 
         let branch = repo.find_branch(&branch_name)?;
@@ -0,0 +1,151 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::{
+    cmd::{
+        commit::SignOpts,
+        common::{self, commit_id, render_applied, Picking},
+    },
+    git::{Commit, Repo},
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+        select,
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Apply the changes of existing commits")]
+pub struct Opts {
+    #[clap(help = "Commits to apply (prompts when omitted)")]
+    revs: Vec<String>,
+
+    #[clap(long, help = "Branch to pick commits from when prompting")]
+    from: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        help = "Parent number to compare against when picking a merge commit"
+    )]
+    mainline: Option<u32>,
+
+    #[clap(
+        long = "continue",
+        conflicts_with = "abort",
+        help = "Commit the resolved conflicts"
+    )]
+    resume: bool,
+
+    #[clap(long, help = "Abort the cherry-pick in progress")]
+    abort: bool,
+
+    #[clap(flatten)]
+    sign: SignOpts,
+}
+
+// Lists the commits of another branch that are not on HEAD, oldest first
+fn select_commits<'r>(repo: &'r Repo, from: &str) -> Result<Vec<Commit<'r>>, Box<dyn Error>> {
+    let branch = repo.find_branch(from)?.target()?;
+    let head = repo.head()?.target()?;
+    let commits = repo.commits_between(head, branch)?;
+
+    if commits.is_empty() {
+        return Err(format!("No commits on {from} that are not on HEAD").into());
+    }
+
+    let items = commits
+        .iter()
+        .map(|commit| {
+            Ok(format!(
+                "{} {}",
+                commit_id(commit)?.to_hex_with_len(7),
+                commit.title()
+            ))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    let selected = select::multi(&items, None)?;
+
+    Ok(commits
+        .into_iter()
+        .zip(items.iter())
+        .filter(|(_, item)| selected.contains(item))
+        .map(|(commit, _)| commit)
+        .rev()
+        .collect())
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+
+    if opts.abort {
+        repo.cherry_pick_head()?
+            .ok_or("No cherry-pick in progress")?;
+        common::backup(&repo, "cherry-pick --abort")?;
+        repo.abort()?;
+        ui.renderln(&message_with_icon(Icon::Check, "Cherry-pick aborted"))?;
+        return Ok(());
+    }
+
+    if opts.resume {
+        let commit = repo
+            .cherry_pick_head()?
+            .ok_or("No cherry-pick in progress")?;
+        let applied = repo.finish_cherry_pick(&commit, opts.sign.mode())?;
+        render_applied(
+            &mut ui,
+            Picking::CherryPick,
+            &commit,
+            commit.title(),
+            applied,
+        )?;
+        return Ok(());
+    }
+
+    let commits = match (opts.revs.is_empty(), &opts.from) {
+        (false, _) => opts
+            .revs
+            .iter()
+            .map(|rev| -> Result<_, Box<dyn Error>> {
+                repo.resolve_commit(rev)?
+                    .ok_or_else(|| format!("Revision not found: {rev}").into())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?,
+        (true, Some(from)) => select_commits(&repo, from)?,
+        (true, None) => return Err("Specify the commits to apply or a branch with --from".into()),
+    };
+
+    // Aborting resets the worktree, so it must not hold changes unrelated to the cherry-pick
+    if repo.has_changes()? {
+        return Err("Commit or stash your changes before cherry-picking".into());
+    }
+
+    for (i, commit) in commits.iter().enumerate() {
+        let applied = repo.cherry_pick(commit, opts.mainline, opts.sign.mode())?;
+
+        if !render_applied(
+            &mut ui,
+            Picking::CherryPick,
+            commit,
+            commit.title(),
+            applied,
+        )? {
+            let remaining = commits[i + 1..]
+                .iter()
+                .map(|commit| Ok(commit_id(commit)?.to_hex_with_len(7).to_string()))
+                .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+            if !remaining.is_empty() {
+                ui.renderln(&dimmed!(text!(format!(
+                    "Not applied yet: src cherry-pick {}",
+                    remaining.join(" ")
+                ))))?;
+            }
+
+            break;
+        }
+    }
+
+    Ok(())
+}
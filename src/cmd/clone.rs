@@ -7,7 +7,7 @@ use crate::{
     progress,
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
     },
 };
 
@@ -61,7 +61,7 @@ pub fn run(opts: Opts) -> Result<(), Box<dyn Error>> {
         .transpose()?
         .ok_or("remote not present")?;
 
-    let mut ui = TermRenderer::default();
+    let mut ui = Renderer::default();
     ui.renderln(&message_with_icon(
         Icon::Check,
         format!("Repository cloned to {}", path.display()),
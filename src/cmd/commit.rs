@@ -1,17 +1,36 @@
 use std::error::Error;
 
-use clap::Parser;
+use clap::{Args, Parser};
 use git2::ErrorCode;
 
 use crate::{
     cmd::add::add_callback,
-    git::{DiffOpts, Repo},
+    git::{DiffOpts, Repo, SignMode},
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
     },
 };
 
+#[derive(Args)]
+pub struct SignOpts {
+    #[clap(short = 'S', long, help = "Sign, regardless of the configuration")]
+    sign: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "sign",
+        help = "Don't sign, regardless of the configuration"
+    )]
+    no_sign: bool,
+}
+
+impl SignOpts {
+    pub fn mode(&self) -> SignMode {
+        SignMode::from_flags(self.sign, self.no_sign)
+    }
+}
+
 #[derive(Parser)]
 #[clap(about = "Record changes to the repository")]
 pub struct Opts {
@@ -21,6 +40,9 @@ pub struct Opts {
     #[clap(short, long, help = "Create a branch")]
     branch: bool,
 
+    #[clap(flatten)]
+    sign: SignOpts,
+
     #[clap(help = "Commit message")]
     pub message: String,
 }
@@ -60,7 +82,7 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
     }
 
     let tree = repo.find_tree(index.write_tree()?)?;
-    let oid = repo.create_commit(&tree, &opts.message, None)?;
+    let oid = repo.create_commit(&tree, &opts.message, None, opts.sign.mode())?;
 
     if old_tree.is_none() {
         repo.create_ref("refs/heads/main", oid)?;
@@ -78,7 +100,7 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
     let diff = repo.diff(opts)?;
     let stats = diff.stats()?;
 
-    let mut ui = TermRenderer::default();
+    let mut ui = Renderer::default();
     let mut children = vec![];
 
     if stats.insertions() > 0 {
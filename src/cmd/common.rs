@@ -0,0 +1,138 @@
+use std::error::Error;
+
+use gix::ObjectId;
+
+use crate::{
+    git::{Applied, Commit, CommitFilter, Repo},
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+        select,
+    },
+};
+
+pub fn commit_id(commit: &Commit<'_>) -> Result<ObjectId, Box<dyn Error>> {
+    Ok(ObjectId::try_from(commit.id().as_bytes())?)
+}
+
+// Resolves the revision or lets the user pick one of the recent commits on the branch
+pub fn select_commit<'r>(repo: &'r Repo, rev: Option<&str>) -> Result<Commit<'r>, Box<dyn Error>> {
+    if let Some(rev) = rev {
+        return Ok(repo.resolve_commit(rev)?.ok_or("Revision not found")?);
+    }
+
+    let filter = CommitFilter {
+        limit: Some(50),
+        first_parent: true,
+        ..Default::default()
+    };
+    let items = repo
+        .commits(&repo.head()?, filter)?
+        .map(|commit| {
+            let commit = commit?;
+
+            Ok(format!(
+                "{} {}",
+                commit_id(&commit)?.to_hex_with_len(7),
+                commit.title()
+            ))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    let selected = select::single(&items, None)?.ok_or("No commit selected")?;
+    let id = selected.split_whitespace().next().unwrap_or_default();
+
+    Ok(repo.resolve_commit(id)?.ok_or("Revision not found")?)
+}
+
+// The commands that apply existing commits and can stop on conflicts
+#[derive(Clone, Copy)]
+pub enum Picking {
+    CherryPick,
+    Revert,
+}
+
+impl Picking {
+    fn command(&self) -> &'static str {
+        match self {
+            Picking::CherryPick => "cherry-pick",
+            Picking::Revert => "revert",
+        }
+    }
+
+    fn empty(&self) -> &'static str {
+        match self {
+            Picking::CherryPick => "(already applied)",
+            Picking::Revert => "(already reverted)",
+        }
+    }
+}
+
+// `title` is the title of the new commit, returns false when stopped on conflicts
+pub fn render_applied(
+    ui: &mut impl Render,
+    picking: Picking,
+    commit: &Commit<'_>,
+    title: &str,
+    applied: Applied,
+) -> Result<bool, Box<dyn Error>> {
+    match applied {
+        Applied::Commit(oid) => {
+            ui.renderln(&block!(
+                icon!(Check).with_status(Status::Success),
+                spacer!(),
+                Node::Attribute(Attribute::CommitShort(ObjectId::try_from(oid.as_bytes())?)),
+                spacer!(),
+                text!(title.to_string())
+            ))?;
+            Ok(true)
+        }
+        Applied::Empty => {
+            ui.renderln(&block!(
+                dimmed!(text!("Skipped")),
+                spacer!(),
+                Node::Attribute(Attribute::CommitShort(commit_id(commit)?)),
+                spacer!(),
+                dimmed!(text!(picking.empty()))
+            ))?;
+            Ok(true)
+        }
+        Applied::Conflict => {
+            let command = picking.command();
+
+            ui.renderln(&multi_line!(
+                block!(
+                    icon!(Cross).with_status(Status::Error),
+                    spacer!(),
+                    text!("Conflict while applying").with_status(Status::Error),
+                    spacer!(),
+                    Node::Attribute(Attribute::CommitShort(commit_id(commit)?))
+                ),
+                continued!(text!(format!(
+                    "Fix conflicts and run 'src {command} --continue', or abort with 'src {command} --abort'"
+                )))
+            ))?;
+
+            Ok(false)
+        }
+    }
+}
+
+pub fn backup(repo: &Repo, reason: &str) -> Result<(), Box<dyn Error>> {
+    backup_with_refs(repo, reason, &[])
+}
+
+// Backups are a safety net, failing to take one is reported without stopping the command
+pub fn backup_with_refs(
+    repo: &Repo,
+    reason: &str,
+    refs: &[(&str, git2::Oid)],
+) -> Result<(), Box<dyn Error>> {
+    if let Err(e) = repo.backup_with_refs(reason, refs) {
+        Renderer::default().renderln(
+            &text!(format!("Unable to back up the current state: {e}"))
+                .with_status(Status::Warning),
+        )?;
+    }
+
+    Ok(())
+}
@@ -70,7 +70,14 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
                 let commit = repo.find_commit(oid)?;
                 let tree = commit.find_tree()?;
 
-                repo.diff(diff_opts.with_all(&tree))?
+                if let Pattern::Stash(_) = pat {
+                    let parent = commit.parent()?.ok_or("stash without parent commit")?;
+                    let parent_tree = parent.find_tree()?;
+
+                    repo.diff(diff_opts.with_trees(&parent_tree, &tree))?
+                } else {
+                    repo.diff(diff_opts.with_all(&tree))?
+                }
             } else {
                 repo.diff(diff_opts.with_pathspec(filter))?
             }
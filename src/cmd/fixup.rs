@@ -0,0 +1,92 @@
+use std::error::Error;
+
+use crate::{
+    cmd::{
+        add::add_callback,
+        commit::SignOpts,
+        common::{self, select_commit},
+    },
+    git::{rewrite, Repo, SignMode},
+    guard::{self, ForceOpts},
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+use clap::Parser;
+
+#[derive(Parser)]
+#[clap(about = "Fold the staged changes into an earlier commit")]
+pub struct Opts {
+    #[clap(help = "Commit to fix (prompts when omitted)")]
+    rev: Option<String>,
+
+    #[clap(short, long, help = "Add all changes")]
+    add_all: bool,
+
+    #[clap(flatten)]
+    sign: SignOpts,
+
+    #[clap(flatten)]
+    force: ForceOpts,
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    // Checked before anything is staged so that a refused fixup leaves the index alone
+    let target = select_commit(&repo, opts.rev.as_deref())?;
+    guard::check_rewrite(&repo, target.id(), &opts.force)?;
+
+    let mut index = repo.index()?;
+
+    if opts.add_all {
+        index.add(["."], add_callback)?;
+        index.write()?;
+    }
+
+    let mut ui = Renderer::default();
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let head = repo.head()?.target()?;
+    let head = repo.find_commit(head)?;
+
+    if tree.0.id() == head.find_tree()?.0.id() {
+        return Err("No staged changes".into());
+    }
+
+    let mut commits = repo.commits_since(&target)?;
+
+    // The fixup commit only exists to be squashed, it's signed when the target is rewritten
+    let config = repo.config()?;
+    let signature = config.user.signature()?;
+    let fixup = repo.write_commit(
+        &config,
+        &signature,
+        &signature,
+        &format!("fixup! {}", target.title()),
+        &tree,
+        &[&head.0],
+        SignMode::Never,
+    )?;
+    commits.push(repo.find_commit(fixup)?);
+
+    let count = commits.len() - 1;
+    let steps = rewrite::autosquash(commits);
+    let onto = target.parent()?.map(|parent| parent.id());
+
+    common::backup(&repo, &format!("fixup {}", target.id()))?;
+
+    let new_head = repo
+        .replay(onto, &steps, opts.sign.mode())?
+        .ok_or("Nothing to rewrite")?;
+    repo.move_head(new_head, &format!("fixup: {}", target.title()))?;
+
+    ui.renderln(&message_with_icon(
+        Icon::Check,
+        format!("Fixed up {}", target.title()),
+    ))?;
+    ui.renderln(&continued!(dimmed!(text!(format!(
+        "Rewrote {count} commit{}",
+        if count == 1 { "" } else { "s" }
+    )))))?;
+
+    Ok(())
+}
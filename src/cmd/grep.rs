@@ -0,0 +1,302 @@
+use std::{error::Error, fs, path::PathBuf, thread};
+
+use clap::Parser;
+use git2::DiffFormat;
+use gix::ObjectId;
+use minus::Pager;
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    git::{CommitFilter, Repo},
+    term::{
+        node::prelude::*,
+        render::{self, Format, Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Search for a pattern in tracked files or history")]
+pub struct Opts {
+    #[clap(help = "Regular expression to search for")]
+    pattern: String,
+
+    #[clap(short, long, help = "Match case insensitively")]
+    ignore_case: bool,
+
+    #[clap(long, help = "Search the staged content instead of the worktree")]
+    cached: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "cached",
+        help = "Search the files of a revision"
+    )]
+    rev: Option<String>,
+
+    #[clap(
+        short = 'S',
+        long,
+        help = "Find commits that change the number of occurrences of the pattern"
+    )]
+    history: bool,
+
+    #[clap(
+        short = 'G',
+        long,
+        conflicts_with = "history",
+        help = "Find commits with added or removed lines matching the pattern"
+    )]
+    history_lines: bool,
+
+    #[clap(short = 'l', long, help = "Only show the names of matching files")]
+    files_with_matches: bool,
+
+    #[clap(long, help = "Disable the pager")]
+    no_pager: bool,
+}
+
+enum Source {
+    Worktree(PathBuf),
+    Blob(ObjectId),
+}
+
+struct File {
+    path: PathBuf,
+    source: Source,
+}
+
+struct Match {
+    path: PathBuf,
+    lines: Vec<(usize, String)>,
+}
+
+fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|&byte| byte == 0)
+}
+
+fn search_file(repo: &gix::Repository, file: &File, regex: &Regex) -> Option<Match> {
+    let content = match &file.source {
+        Source::Worktree(path) => fs::read(path).ok()?,
+        Source::Blob(id) => repo.find_object(*id).ok()?.detach().data,
+    };
+
+    if is_binary(&content) {
+        return None;
+    }
+
+    let lines = String::from_utf8_lossy(&content)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| regex.is_match(line))
+        .map(|(i, line)| (i + 1, line.to_string()))
+        .collect::<Vec<_>>();
+
+    (!lines.is_empty()).then(|| Match {
+        path: file.path.clone(),
+        lines,
+    })
+}
+
+// Files are split into one chunk per CPU, each thread gets its own handle to the object database
+fn search(repo: gix::ThreadSafeRepository, files: &[File], regex: &Regex) -> Vec<Match> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = files.len().div_ceil(threads).max(1);
+
+    thread::scope(|scope| {
+        let handles = files
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let repo = &repo;
+
+                scope.spawn(move || {
+                    let repo = repo.to_thread_local();
+
+                    chunk
+                        .iter()
+                        .filter_map(|file| search_file(&repo, file, regex))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+fn files(repo: &Repo, opts: &Opts) -> Result<Vec<File>, Box<dyn Error>> {
+    let blob = |(path, oid): (PathBuf, git2::Oid)| -> Result<File, Box<dyn Error>> {
+        Ok(File {
+            path,
+            source: Source::Blob(ObjectId::try_from(oid.as_bytes())?),
+        })
+    };
+
+    if let Some(rev) = &opts.rev {
+        let commit = repo.resolve_commit(rev)?.ok_or("Revision not found")?;
+        return commit.find_tree()?.files()?.into_iter().map(blob).collect();
+    }
+
+    let files = repo.index()?.files();
+
+    if opts.cached {
+        return files.into_iter().map(blob).collect();
+    }
+
+    let root = repo.workdir().ok_or("Repository has no worktree")?;
+
+    Ok(files
+        .into_iter()
+        .map(|(path, _)| File {
+            source: Source::Worktree(root.join(&path)),
+            path,
+        })
+        .collect())
+}
+
+fn highlight(line: &str, regex: &Regex) -> Node {
+    let mut children = vec![];
+    let mut last = 0;
+
+    for m in regex.find_iter(line) {
+        children.push(text!(line[last..m.start()].to_string()));
+        children.push(text!(m.as_str().to_string()).with_status(Status::Warning));
+        last = m.end();
+    }
+
+    children.push(text!(line[last..].to_string()));
+    Node::Block(children)
+}
+
+fn render_matches(
+    ui: &mut impl Render,
+    matches: Vec<Match>,
+    regex: &Regex,
+    opts: &Opts,
+) -> Result<(), Box<dyn Error>> {
+    for Match { path, lines } in matches {
+        if opts.files_with_matches {
+            ui.renderln(&text!(path.display().to_string()))?;
+            continue;
+        }
+
+        let width = lines.last().map_or(1, |(n, _)| n.to_string().len());
+
+        ui.renderln(&Node::Group(
+            path.display().to_string().into(),
+            Some(lines.len()),
+            Box::new(Node::MultiLine(
+                lines
+                    .into_iter()
+                    .map(|(n, line)| {
+                        block!(
+                            dimmed!(text!(format!("{n:>width$}"))),
+                            spacer!(),
+                            highlight(&line, regex)
+                        )
+                    })
+                    .collect(),
+            )),
+        ))?;
+    }
+
+    Ok(())
+}
+
+// Compares the added and removed lines of a commit, a pure move of a matching line is not a change
+fn changes_pattern(
+    diff: &git2::Diff,
+    regex: &Regex,
+    lines_mode: bool,
+) -> Result<bool, git2::Error> {
+    let (mut added, mut removed) = (0, 0);
+
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        let content = String::from_utf8_lossy(line.content());
+        let count = if lines_mode {
+            usize::from(regex.is_match(&content))
+        } else {
+            regex.find_iter(&content).count()
+        };
+
+        match line.origin() {
+            '+' => added += count,
+            '-' => removed += count,
+            _ => {}
+        }
+
+        true
+    })?;
+
+    Ok(if lines_mode {
+        added + removed > 0
+    } else {
+        added != removed
+    })
+}
+
+fn render_history(
+    ui: &mut impl Render,
+    repo: &Repo,
+    regex: &Regex,
+    opts: &Opts,
+) -> Result<(), Box<dyn Error>> {
+    for commit in repo.commits(&repo.head()?, CommitFilter::default())? {
+        let commit = commit?;
+
+        if !changes_pattern(&repo.diff_commit(&commit)?, regex, opts.history_lines)? {
+            continue;
+        }
+
+        ui.renderln(&block!(
+            Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+                commit.id().as_bytes()
+            )?)),
+            spacer!(),
+            Node::text_fill(
+                commit
+                    .message()
+                    .unwrap_or_default()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+            ),
+            right_aligned!(dimmed!(text!(commit.time().format("%Y-%m-%d").to_string())))
+        ))?;
+    }
+
+    Ok(())
+}
+
+fn render(mut ui: impl Render, repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let regex = RegexBuilder::new(&opts.pattern)
+        .case_insensitive(opts.ignore_case)
+        .build()?;
+
+    if opts.history || opts.history_lines {
+        return render_history(&mut ui, &repo, &regex, &opts);
+    }
+
+    let files = files(&repo, &opts)?;
+    let matches = search(gix::open(repo.path())?.into_sync(), &files, &regex);
+
+    render_matches(&mut ui, matches, &regex, &opts)
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    if opts.no_pager || render::format() == Format::Json {
+        render(Renderer::default(), repo, opts)
+    } else {
+        render::enable_pager_color();
+
+        let mut pager = Pager::new();
+        pager.set_prompt(format!("grep {}, q to quit", opts.pattern))?;
+
+        render(Renderer::new(&mut pager), repo, opts)?;
+        minus::page_all(pager)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, ValueHint};
+use git2::{Delta, Diff, DiffFormat};
+use gix::ObjectId;
+use minus::Pager;
+
+use crate::{
+    git::{CommitFilter, Repo},
+    term::{
+        node::prelude::*,
+        render::{self, Format, Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Show the history of a file")]
+pub struct Opts {
+    #[clap(value_hint = ValueHint::FilePath, help = "File to show the history of")]
+    path: PathBuf,
+
+    #[clap(short = 'n', long = "max-count", help = "Limit the number of commits")]
+    limit: Option<usize>,
+
+    #[clap(long, help = "Disable the pager")]
+    no_pager: bool,
+}
+
+fn touches(delta: &git2::DiffDelta<'_>, path: &Path) -> bool {
+    delta.new_file().path() == Some(path) || delta.old_file().path() == Some(path)
+}
+
+fn patch(diff: &Diff, path: &Path) -> Result<Node, git2::Error> {
+    let mut lines = vec![];
+
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        if !touches(&delta, path) {
+            return true;
+        }
+
+        let content = String::from_utf8_lossy(line.content());
+        let content = content.trim_end_matches('\n');
+
+        lines.push(match line.origin() {
+            '+' => text!(format!("+{content}")).with_status(Status::Success),
+            '-' => text!(format!("-{content}")).with_status(Status::Error),
+            ' ' => text!(format!(" {content}")),
+            _ => dimmed!(text!(content.to_string())),
+        });
+
+        true
+    })?;
+
+    Ok(Node::MultiLine(lines))
+}
+
+fn render(mut ui: impl Render, repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut path = repo.relative_path(&opts.path);
+    let head = repo.head()?;
+    let filter = CommitFilter {
+        paths: vec![path.clone()],
+        limit: opts.limit,
+        ..Default::default()
+    };
+
+    for commit in repo.commits(&head, filter)? {
+        let commit = commit?;
+        let diff = repo.diff_commit(&commit)?;
+
+        ui.renderln(&multi_line!(
+            block!(
+                Node::Attribute(Attribute::Commit(ObjectId::try_from(
+                    commit.id().as_bytes()
+                )?)),
+                spacer!(),
+                dimmed!(text!(path.display().to_string()))
+            ),
+            dimmed!(commit.headers_ui()),
+            spacer!(),
+            text!(commit.message_formatted()),
+            spacer!(),
+            patch(&diff, &path)?,
+            Node::Empty
+        ))?;
+
+        // Older commits know the file by its previous name
+        let renamed_from = diff
+            .deltas()
+            .find(|delta| {
+                delta.status() == Delta::Renamed && delta.new_file().path() == Some(path.as_path())
+            })
+            .and_then(|delta| delta.old_file().path().map(Path::to_path_buf));
+
+        if let Some(old_path) = renamed_from {
+            path = old_path;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    if opts.no_pager || render::format() == Format::Json {
+        render(Renderer::default(), repo, opts)
+    } else {
+        render::enable_pager_color();
+
+        let mut pager = Pager::new();
+        pager.set_prompt(format!("history {}, q to quit", opts.path.display()))?;
+
+        render(Renderer::new(&mut pager), repo, opts)?;
+        minus::page_all(pager)?;
+
+        Ok(())
+    }
+}
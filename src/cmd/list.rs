@@ -1,14 +1,17 @@
-use std::error::Error;
+use std::{error::Error, path::PathBuf};
 
-use clap::Parser;
+use chrono::{DateTime, Local};
+use clap::{Args, Parser};
 use gix::ObjectId;
 use minus::Pager;
+use regex::Regex;
 
 use crate::{
-    git::{Commit, Repo},
+    git::{parse_date, reflog::Entry, Commit, CommitFilter, Repo, Verification},
     term::{
+        self,
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{self, Format, Render, Renderer},
     },
 };
 
@@ -21,6 +24,9 @@ pub struct Opts {
     #[clap(long, help = "Disable the pager")]
     no_pager: bool,
 
+    #[clap(long, help = "Verify and show the signature of each commit")]
+    show_signature: bool,
+
     #[clap(subcommand)]
     cmd: Option<Cmd>,
 }
@@ -34,6 +40,9 @@ pub enum Cmd {
     Commit {
         #[clap(help = "Target branch or tag")]
         target: Option<String>,
+
+        #[clap(flatten)]
+        filter: FilterOpts,
     },
 
     #[clap(about = "List remotes")]
@@ -41,6 +50,70 @@ pub enum Cmd {
 
     #[clap(about = "List branches")]
     Branch,
+
+    #[clap(about = "List the operations recorded in HEAD's reflog")]
+    Reflog,
+
+    #[clap(about = "List the backups made before destructive operations")]
+    Backup,
+}
+
+#[derive(Args)]
+pub struct FilterOpts {
+    #[clap(long, help = "Only show commits by authors matching the pattern")]
+    author: Option<Regex>,
+
+    #[clap(long, help = "Only show commits with a message matching the pattern")]
+    grep: Option<Regex>,
+
+    #[clap(long, value_parser = parse_date, help = "Only show commits after a date (e.g. 2024-01-31 or '2 weeks ago')")]
+    since: Option<DateTime<Local>>,
+
+    #[clap(long, value_parser = parse_date, help = "Only show commits before a date")]
+    until: Option<DateTime<Local>>,
+
+    #[clap(short = 'n', long = "max-count", help = "Limit the number of commits")]
+    limit: Option<usize>,
+
+    #[clap(long, conflicts_with = "no_merges", help = "Only show merge commits")]
+    merges: bool,
+
+    #[clap(long, help = "Leave out merge commits")]
+    no_merges: bool,
+
+    #[clap(long, help = "Only follow the first parent of merge commits")]
+    first_parent: bool,
+
+    #[clap(
+        last = true,
+        help = "Only show commits touching these paths (a single file is followed across renames)"
+    )]
+    paths: Vec<PathBuf>,
+}
+
+impl FilterOpts {
+    fn into_filter(self, repo: &Repo) -> CommitFilter {
+        let paths = self
+            .paths
+            .iter()
+            .map(|path| repo.relative_path(path))
+            .collect();
+
+        CommitFilter {
+            author: self.author,
+            grep: self.grep,
+            since: self.since,
+            until: self.until,
+            limit: self.limit,
+            merges: match (self.merges, self.no_merges) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            first_parent: self.first_parent,
+            paths,
+        }
+    }
 }
 
 impl Cmd {
@@ -50,6 +123,8 @@ impl Cmd {
             Cmd::Commit { .. } => "commit",
             Cmd::Remote => "remote",
             Cmd::Branch => "branch",
+            Cmd::Reflog => "reflog",
+            Cmd::Backup => "backup",
         }
     }
 }
@@ -71,27 +146,37 @@ fn list_remotes(ui: &mut impl Render, repo: &mut Repo) -> Result<(), Box<dyn Err
 
 fn list_commits<'a>(
     ui: &mut impl Render,
+    repo: &Repo,
     walk: impl Iterator<Item = Result<Commit<'a>, git2::Error>>,
     short: bool,
+    show_signature: bool,
 ) -> Result<(), Box<dyn Error>> {
+    // Verifying runs the signing program for every commit, so it's only done when asked for
+    let config = show_signature.then(|| repo.config()).transpose()?;
+
     for commit in walk {
         let commit = commit?;
 
-        if commit.is_signed() {
-            ui.render(&block!(icon!(Lock).with_status(Status::Success), spacer!()))?;
-        } else if short {
-            ui.render(&spacer!())?;
+        if let Some(config) = &config {
+            match repo.verify_commit(config, &commit)? {
+                Verification::Unsigned if short => ui.render(&spacer!())?,
+                Verification::Unsigned => {}
+                verification => ui.render(&block!(verification.icon(), spacer!()))?,
+            }
         }
 
-        ui.render(&Node::Attribute(Attribute::Commit(ObjectId::try_from(
-            commit.id().as_bytes(),
-        )?)))?;
-
+        let id = ObjectId::try_from(commit.id().as_bytes())?;
         let message = commit.message().unwrap_or_default().trim();
 
         if short {
-            ui.renderln(&Node::text_head_1(message))?;
+            ui.renderln(&block!(
+                Node::Attribute(Attribute::CommitShort(id)),
+                spacer!(),
+                Node::text_fill(message.lines().next().unwrap_or_default()),
+                right_aligned!(dimmed!(text!(commit.time().format("%Y-%m-%d").to_string())))
+            ))?;
         } else {
+            ui.render(&Node::Attribute(Attribute::Commit(id)))?;
             ui.renderln(&multi_line!(
                 Node::Empty,
                 dimmed!(commit.headers_ui()),
@@ -115,36 +200,100 @@ fn list_branches(ui: &mut impl Render, repo: Repo) -> Result<(), Box<dyn Error>>
     Ok(())
 }
 
+pub fn reflog_entry(entry: &Entry) -> Result<Node, Box<dyn Error>> {
+    Ok(block!(
+        Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+            entry.new.as_bytes()
+        )?)),
+        spacer!(),
+        Node::Attribute(Attribute::Operation(entry.operation().name().into())),
+        spacer!(),
+        Node::text_fill(entry.summary().to_string()),
+        right_aligned!(dimmed!(text!(term::relative_time(entry.time))))
+    ))
+}
+
+fn list_reflog(ui: &mut impl Render, repo: Repo) -> Result<(), Box<dyn Error>> {
+    for entry in repo.reflog()? {
+        ui.renderln(&reflog_entry(&entry)?)?;
+    }
+
+    Ok(())
+}
+
+fn list_backups(ui: &mut impl Render, repo: Repo) -> Result<(), Box<dyn Error>> {
+    for backup in repo.backups()? {
+        let head = match backup.head() {
+            Some(head) => {
+                Node::Attribute(Attribute::CommitShort(ObjectId::try_from(head.as_bytes())?))
+            }
+            None => dimmed!(text!("unborn")),
+        };
+
+        ui.renderln(&block!(
+            text!(backup.name),
+            spacer!(),
+            head,
+            spacer!(),
+            Node::text_fill(backup.reason),
+            right_aligned!(dimmed!(text!(term::relative_time(backup.time))))
+        ))?;
+    }
+
+    Ok(())
+}
+
 fn render(mut ui: impl Render, mut repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
     match opts.cmd {
         Some(cmd) => match cmd {
             Cmd::Branch => list_branches(&mut ui, repo),
             Cmd::Remote => list_remotes(&mut ui, &mut repo),
-            Cmd::Stash => list_commits(&mut ui, repo.stashes()?, opts.short),
-            Cmd::Commit { target } => {
+            Cmd::Reflog => list_reflog(&mut ui, repo),
+            Cmd::Backup => list_backups(&mut ui, repo),
+            Cmd::Stash => list_commits(
+                &mut ui,
+                &repo,
+                repo.stashes()?,
+                opts.short,
+                opts.show_signature,
+            ),
+            Cmd::Commit { target, filter } => {
                 let target = match target {
                     Some(target) => repo.find_branch(&target).map(|b| b.into_ref()),
                     None => repo.head(),
                 }?;
+                let filter = filter.into_filter(&repo);
 
-                list_commits(&mut ui, repo.commits(&target)?, opts.short)
+                list_commits(
+                    &mut ui,
+                    &repo,
+                    repo.commits(&target, filter)?,
+                    opts.short,
+                    opts.show_signature,
+                )
             }
         },
-        None => list_commits(&mut ui, repo.commits(&repo.head()?)?, opts.short),
+        None => list_commits(
+            &mut ui,
+            &repo,
+            repo.commits(&repo.head()?, CommitFilter::default())?,
+            opts.short,
+            opts.show_signature,
+        ),
     }
 }
 
 pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
-    if opts.no_pager {
-        render(TermRenderer::default(), repo, opts)
+    if opts.no_pager || render::format() == Format::Json {
+        render(Renderer::default(), repo, opts)
     } else {
-        colored::control::set_override(true);
+        render::enable_pager_color();
 
         let cmd = opts.cmd.as_ref().map(Cmd::name).unwrap_or("commit");
         let mut pager = Pager::new();
         pager.set_prompt(format!("list {cmd}s, q to quit"))?;
 
-        render(TermRenderer::new(&mut pager), repo, opts)?;
+        render(Renderer::new(&mut pager), repo, opts)?;
         minus::page_all(pager)?;
 
         Ok(())
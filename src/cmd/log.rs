@@ -0,0 +1,177 @@
+use std::{collections::HashMap, error::Error};
+
+use clap::{Parser, ValueEnum};
+use git2::Sort;
+use gix::ObjectId;
+use minus::Pager;
+
+use crate::{
+    git::Repo,
+    term::{
+        lanes::Lanes,
+        node::prelude::*,
+        render::{self, Format, Render, Renderer},
+    },
+};
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum Order {
+    #[default]
+    Topo,
+    Date,
+}
+
+impl From<Order> for Sort {
+    fn from(order: Order) -> Self {
+        match order {
+            Order::Topo => Sort::TOPOLOGICAL | Sort::TIME,
+            Order::Date => Sort::TIME,
+        }
+    }
+}
+
+#[derive(Parser)]
+#[clap(about = "Show the commit history")]
+pub struct Opts {
+    #[clap(long, help = "Draw the commit graph")]
+    graph: bool,
+
+    #[clap(long, help = "Show commits reachable from all branches and tags")]
+    all: bool,
+
+    #[clap(long, value_enum, default_value = "topo", help = "Commit ordering")]
+    order: Order,
+
+    #[clap(long, help = "Disable the pager")]
+    no_pager: bool,
+
+    #[clap(help = "Branches, tags or commits to start from (defaults to HEAD)")]
+    targets: Vec<String>,
+}
+
+fn decorations(repo: &Repo) -> Result<HashMap<git2::Oid, Vec<Node>>, Box<dyn Error>> {
+    let mut decorations = HashMap::<_, Vec<Node>>::new();
+
+    for reference in repo.references()? {
+        let reference = reference?;
+        let name = reference.name()?;
+
+        let attr = if let Some(name) = name.strip_prefix("refs/heads/") {
+            Attribute::Branch(name.to_string().into())
+        } else if let Some(name) = name.strip_prefix("refs/remotes/") {
+            Attribute::Remote(name.to_string().into())
+        } else if let Some(name) = name.strip_prefix("refs/tags/") {
+            Attribute::Tag(name.to_string().into())
+        } else {
+            continue;
+        };
+
+        // Tags can point to other objects than commits
+        let Ok(commit) = reference.find_commit() else {
+            continue;
+        };
+
+        decorations
+            .entry(commit.id())
+            .or_default()
+            .push(Node::Attribute(attr));
+    }
+
+    Ok(decorations)
+}
+
+fn tips(repo: &Repo, opts: &Opts) -> Result<Vec<git2::Oid>, Box<dyn Error>> {
+    let mut tips = vec![];
+
+    for target in &opts.targets {
+        let commit = repo
+            .resolve_commit(target)?
+            .ok_or_else(|| format!("Revision not found: {target}"))?;
+        tips.push(commit.id());
+    }
+
+    if opts.all {
+        for reference in repo.references()? {
+            if let Ok(commit) = reference?.find_commit() {
+                tips.push(commit.id());
+            }
+        }
+    }
+
+    if tips.is_empty() || opts.all {
+        tips.push(repo.head()?.find_commit()?.id());
+    }
+
+    Ok(tips)
+}
+
+fn render(mut ui: impl Render, repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let glyphs = &render::theme().glyphs;
+    let graph = opts.graph && render::format() == Format::Text;
+    let mut decorations = decorations(&repo)?;
+    let mut lanes = Lanes::default();
+
+    for commit in repo.walk(&tips(&repo, &opts)?, opts.order.into())? {
+        let commit = commit?;
+        let mut row = vec![];
+
+        if graph {
+            row.push(text!(lanes
+                .next(&commit.id(), &commit.parent_ids())
+                .render(glyphs)));
+        }
+
+        row.push(Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+            commit.id().as_bytes(),
+        )?)));
+        row.push(spacer!());
+
+        if let Some(refs) = decorations.remove(&commit.id()) {
+            let mut children = vec![];
+
+            for (i, node) in refs.into_iter().enumerate() {
+                if i > 0 {
+                    children.push(text!(", "));
+                }
+
+                children.push(node);
+            }
+
+            row.push(label!(Node::Block(children)));
+            row.push(spacer!());
+        }
+
+        row.push(Node::text_fill(
+            commit
+                .message()
+                .unwrap_or_default()
+                .lines()
+                .next()
+                .unwrap_or_default(),
+        ));
+        row.push(right_aligned!(dimmed!(text!(commit
+            .time()
+            .format("%Y-%m-%d")
+            .to_string()))));
+
+        ui.renderln(&Node::Block(row))?;
+    }
+
+    Ok(())
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    if opts.no_pager || render::format() == Format::Json {
+        render(Renderer::default(), repo, opts)
+    } else {
+        render::enable_pager_color();
+
+        let mut pager = Pager::new();
+        pager.set_prompt("log, q to quit")?;
+
+        render(Renderer::new(&mut pager), repo, opts)?;
+        minus::page_all(pager)?;
+
+        Ok(())
+    }
+}
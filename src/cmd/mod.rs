@@ -1,15 +1,31 @@
+pub mod absorb;
 pub mod add;
 pub mod amend;
+pub mod blame;
 pub mod branch;
 pub mod checkout;
+pub mod cherry_pick;
 pub mod clone;
 pub mod commit;
+pub mod common;
 pub mod diff;
 pub mod fetch;
+pub mod fixup;
+pub mod grep;
+pub mod history;
 pub mod list;
+pub mod log;
 pub mod pull;
 pub mod push;
+pub mod reset;
+pub mod restore_backup;
+pub mod revert;
+pub mod reword;
+pub mod split;
 pub mod stash;
 pub mod status;
 pub mod sync;
+pub mod tag;
+pub mod undo;
 pub mod unstash;
+pub mod verify;
@@ -6,7 +6,7 @@ use crate::{
     git::{RemoteOpts, Repo},
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
         setup_progress_bar,
     },
 };
@@ -47,9 +47,10 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
         let (analysis, _) = repo.merge_analysis(&upstream)?;
 
         if analysis.is_up_to_date() {
-            let mut ui = TermRenderer::default();
+            let mut ui = Renderer::default();
             return Ok(ui.renderln(&message_with_icon(Icon::Check, "up to date"))?);
         } else if analysis.is_fast_forward() {
+            // Only fast-forwards are supported, so pulling never creates a commit that needs signing
             let target = head.set_target(oid, "fast-forward")?;
             repo.checkout_tree(&target.find_tree()?, true)?;
         } else {
@@ -5,10 +5,11 @@ use colored::Colorize;
 use git2::ErrorCode;
 
 use crate::{
+    cmd::common,
     git::{Branch, Config, RemoteOpts, Repo},
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
         setup_progress_bar,
     },
 };
@@ -55,7 +56,7 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
     let target = branch.upstream()?.target()?;
     let remote_name = upstream.remote_name()?;
     let mut remote = repo.find_remote(remote_name)?;
-    let mut ui = TermRenderer::default();
+    let mut ui = Renderer::default();
 
     ui.renderln(&block!(
         text!("Pushing to: "),
@@ -65,6 +66,17 @@ pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
         )
     ))?;
 
+    // The commits overwritten on the remote are the ones of the remote-tracking branch
+    if opts.force {
+        let tracking = format!("refs/remotes/{}", upstream.name()?);
+
+        common::backup_with_refs(
+            &repo,
+            &format!("push --force to {remote_name}"),
+            &[(&tracking, target)],
+        )?;
+    }
+
     let (tx, rx) = std::sync::mpsc::channel();
     let handle = setup_progress_bar(rx);
     let reply = remote.push(
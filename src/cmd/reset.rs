@@ -0,0 +1,166 @@
+use std::error::Error;
+
+use clap::{ArgGroup, Parser};
+use git2::{Delta, ResetType};
+use gix::ObjectId;
+
+use crate::{
+    cmd::common,
+    git::{Commit, DiffOpts, Repo},
+    guard::{self, ForceOpts},
+    term::{
+        self,
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(
+    about = "Move HEAD to another commit",
+    group(ArgGroup::new("mode").args(["soft", "mixed", "hard"]))
+)]
+pub struct Opts {
+    #[clap(help = "Commit to move to (e.g. HEAD~2 or a branch)")]
+    rev: String,
+
+    #[clap(long, help = "Keep the index and the worktree")]
+    soft: bool,
+
+    #[clap(long, help = "Reset the index but keep the worktree (default)")]
+    mixed: bool,
+
+    #[clap(long, help = "Reset the index and overwrite the worktree")]
+    hard: bool,
+
+    #[clap(short, long, help = "Reset without prompting")]
+    yes: bool,
+
+    #[clap(flatten)]
+    force: ForceOpts,
+}
+
+impl Opts {
+    fn kind(&self) -> ResetType {
+        match (self.soft, self.hard) {
+            (true, _) => ResetType::Soft,
+            (_, true) => ResetType::Hard,
+            _ => ResetType::Mixed,
+        }
+    }
+}
+
+fn render_dropped(ui: &mut impl Render, dropped: &[Commit<'_>]) -> Result<(), Box<dyn Error>> {
+    if dropped.is_empty() {
+        return Ok(());
+    }
+
+    let lines = dropped
+        .iter()
+        .map(|commit| {
+            Ok(block!(
+                Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+                    commit.id().as_bytes()
+                )?)),
+                spacer!(),
+                Node::text_fill(commit.title())
+            ))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+    ui.renderln(&Node::Group(
+        "Commits no longer on the branch".into(),
+        Some(lines.len()),
+        Box::new(Node::MultiLine(lines)),
+    ))?;
+
+    Ok(())
+}
+
+// Files whose worktree content differs from the target, untracked files are left alone
+fn render_overwritten(
+    ui: &mut impl Render,
+    repo: &Repo,
+    target: git2::Oid,
+) -> Result<(), Box<dyn Error>> {
+    let tree = repo.find_commit(target)?.find_tree()?;
+    let diff = repo.diff(DiffOpts::default().with_all(&tree))?;
+    let lines = diff
+        .deltas()
+        .filter_map(|delta| {
+            let indicator = match delta.status() {
+                Delta::Untracked | Delta::Ignored | Delta::Unmodified => return None,
+                Delta::Added => Indicator::New,
+                Delta::Deleted => Indicator::Deleted,
+                Delta::Renamed => Indicator::Renamed,
+                _ => Indicator::Modified,
+            };
+            let path = delta.new_file().path().or(delta.old_file().path())?;
+
+            Some(block!(
+                Node::Indicator(indicator),
+                spacer!(),
+                text!(path.display().to_string())
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    ui.renderln(&Node::Group(
+        "Files that will be overwritten".into(),
+        Some(lines.len()),
+        Box::new(Node::MultiLine(lines)),
+    ))?;
+
+    Ok(())
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+    let commit = repo
+        .resolve_commit(&opts.rev)?
+        .ok_or("Revision not found")?;
+    let kind = opts.kind();
+    let dropped = repo.commits_between(commit.id(), repo.head()?.target()?)?;
+
+    // Dropping a commit that's on the upstream means force pushing later, it's enough to check
+    // the oldest dropped commits since everything after them is dropped too
+    for oldest in dropped.iter().filter(|commit| {
+        !commit
+            .0
+            .parent_ids()
+            .any(|parent| dropped.iter().any(|c| c.id() == parent))
+    }) {
+        guard::check_rewrite(&repo, oldest.id(), &opts.force)?;
+    }
+
+    render_dropped(&mut ui, &dropped)?;
+
+    if kind == ResetType::Hard {
+        render_overwritten(&mut ui, &repo, commit.id())?;
+
+        if !opts.yes && !term::confirm("Discard the changes and reset?")? {
+            return Ok(());
+        }
+    }
+
+    common::backup(&repo, &format!("reset {}", opts.rev))?;
+    repo.reset(&commit, kind)?;
+
+    ui.renderln(&block!(
+        icon!(Check).with_status(Status::Success),
+        spacer!(),
+        text!("HEAD is now at"),
+        spacer!(),
+        Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+            commit.id().as_bytes()
+        )?)),
+        spacer!(),
+        Node::text_fill(commit.title())
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::{
+    git::Repo,
+    term::{
+        self,
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Restore HEAD, the index and the worktree from a backup")]
+pub struct Opts {
+    #[clap(help = "Backup to restore (see 'src list backup')")]
+    id: String,
+
+    #[clap(short, long, help = "Restore without prompting")]
+    yes: bool,
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+
+    if !opts.yes && !term::confirm("Overwrite the worktree with this backup?")? {
+        return Ok(());
+    }
+
+    let refs = repo.find_backup(&opts.id)?.refs;
+    let current = repo.restore_backup(&opts.id)?;
+
+    ui.renderln(&message_with_icon(
+        Icon::Check,
+        format!("Restored {}", opts.id),
+    ))?;
+
+    // Other refs aren't restored since they might belong to a remote, but their commits are kept
+    for (name, oid) in refs {
+        ui.renderln(&continued!(text!(format!("{name} was at {oid}"))))?;
+    }
+
+    ui.renderln(&continued!(dimmed!(text!(format!(
+        "The previous state was saved as backup {current}"
+    )))))?;
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::{
+    cmd::{
+        commit::SignOpts,
+        common::{self, render_applied, Picking},
+    },
+    git::Repo,
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Create a commit that undoes the changes of another commit")]
+pub struct Opts {
+    #[clap(help = "Commit to revert (defaults to HEAD)")]
+    rev: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        help = "Parent number to keep when reverting a merge commit"
+    )]
+    mainline: Option<u32>,
+
+    #[clap(
+        long = "continue",
+        conflicts_with = "abort",
+        help = "Commit the resolved conflicts"
+    )]
+    resume: bool,
+
+    #[clap(long, help = "Abort the revert in progress")]
+    abort: bool,
+
+    #[clap(flatten)]
+    sign: SignOpts,
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+
+    if opts.abort {
+        repo.revert_head()?.ok_or("No revert in progress")?;
+        common::backup(&repo, "revert --abort")?;
+        repo.abort()?;
+        ui.renderln(&message_with_icon(Icon::Check, "Revert aborted"))?;
+        return Ok(());
+    }
+
+    if opts.resume {
+        let commit = repo.revert_head()?.ok_or("No revert in progress")?;
+        let applied = repo.finish_revert(&commit, opts.sign.mode())?;
+        let title = format!("revert: {}", commit.title());
+
+        return render_applied(&mut ui, Picking::Revert, &commit, &title, applied).map(|_| ());
+    }
+
+    let rev = opts.rev.as_deref().unwrap_or("HEAD");
+    let commit = repo.resolve_commit(rev)?.ok_or("Revision not found")?;
+
+    if commit.0.parent_count() > 1 && opts.mainline.is_none() {
+        return Err("The commit is a merge, pick the parent to keep with -m <parent>".into());
+    }
+
+    // Aborting resets the worktree, so it must not hold changes unrelated to the revert
+    if repo.has_changes()? {
+        return Err("Commit or stash your changes before reverting".into());
+    }
+
+    let applied = repo.revert(&commit, opts.mainline, opts.sign.mode())?;
+    let title = format!("revert: {}", commit.title());
+
+    render_applied(&mut ui, Picking::Revert, &commit, &title, applied).map(|_| ())
+}
@@ -0,0 +1,87 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::{
+    cmd::{
+        commit::SignOpts,
+        common::{self, select_commit},
+    },
+    git::{rewrite::Step, Repo},
+    guard::{self, ForceOpts},
+    term::{
+        self,
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Change the message of a commit on the current branch")]
+pub struct Opts {
+    #[clap(help = "Commit to reword (prompts when omitted)")]
+    rev: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        help = "New commit message (opens the editor when omitted)"
+    )]
+    message: Option<String>,
+
+    #[clap(flatten)]
+    force: ForceOpts,
+
+    #[clap(flatten)]
+    sign: SignOpts,
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+    let target = select_commit(&repo, opts.rev.as_deref())?;
+
+    guard::check_rewrite(&repo, target.id(), &opts.force)?;
+
+    let message = match opts.message {
+        Some(message) => message,
+        None => term::edit(
+            &repo.path().join("COMMIT_EDITMSG"),
+            &format!(
+                "{}\n\n# Enter the new commit message, lines starting with '#' are ignored\n",
+                target.message()?.trim()
+            ),
+            repo.config()?.core.editor.as_deref(),
+        )?,
+    };
+
+    if message.trim().is_empty() {
+        return Err("Aborting due to an empty commit message".into());
+    }
+
+    let mut commits = repo.commits_since(&target)?.into_iter();
+    let mut steps = vec![];
+
+    if let Some(commit) = commits.next() {
+        steps.push(Step::Reword(commit, message.clone()));
+    }
+
+    steps.extend(commits.map(Step::Pick));
+
+    common::backup(&repo, &format!("reword {}", target.id()))?;
+
+    let onto = target.parent()?.map(|parent| parent.id());
+    let new_head = repo
+        .replay(onto, &steps, opts.sign.mode())?
+        .ok_or("Nothing to rewrite")?;
+
+    // Only messages change so the index and the worktree stay as they are
+    repo.head()?
+        .set_target(new_head, &format!("reword: {}", target.title()))?;
+
+    ui.renderln(&message_with_icon(
+        Icon::Check,
+        format!("Reworded {}", message.lines().next().unwrap_or_default()),
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::{error::Error, path::PathBuf};
+
+use clap::Parser;
+use git2::{Delta, FileMode, Oid};
+use gix::ObjectId;
+
+use crate::{
+    cmd::{
+        commit::SignOpts,
+        common::{self, select_commit},
+    },
+    git::{rewrite::Step, DiffOpts, Repo, Tree},
+    guard::{self, ForceOpts},
+    term::{
+        self,
+        node::prelude::*,
+        render::{Render, Renderer},
+        select,
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Split a commit into several commits, whole files at a time")]
+pub struct Opts {
+    #[clap(help = "Commit to split (prompts when omitted)")]
+    rev: Option<String>,
+
+    #[clap(flatten)]
+    sign: SignOpts,
+
+    #[clap(flatten)]
+    force: ForceOpts,
+}
+
+// A file that differs between two trees, with its entry in the new tree or `None` when deleted
+type Change = (PathBuf, Option<(Oid, FileMode)>);
+
+fn changed_files(repo: &Repo, old: &Tree<'_>, new: &Tree<'_>) -> Result<Vec<Change>, git2::Error> {
+    let diff = repo.diff(DiffOpts::default().with_trees(old, new))?;
+    let mut changes = vec![];
+
+    for delta in diff.deltas() {
+        let (old_file, new_file) = (delta.old_file(), delta.new_file());
+
+        match (delta.status(), old_file.path(), new_file.path()) {
+            (Delta::Deleted, Some(path), _) => changes.push((path.to_path_buf(), None)),
+            (Delta::Renamed, Some(old_path), Some(new_path)) => {
+                changes.push((old_path.to_path_buf(), None));
+                changes.push((
+                    new_path.to_path_buf(),
+                    Some((new_file.id(), new_file.mode())),
+                ));
+            }
+            (_, _, Some(path)) => {
+                changes.push((path.to_path_buf(), Some((new_file.id(), new_file.mode()))))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(changes)
+}
+
+fn is_clean(repo: &Repo, tree: &Tree<'_>) -> Result<bool, git2::Error> {
+    Ok(repo
+        .diff(DiffOpts::default().with_all(tree))?
+        .deltas()
+        .all(|delta| delta.status() == Delta::Untracked))
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+    let head = repo.find_commit(repo.head()?.target()?)?;
+
+    if !is_clean(&repo, &head.find_tree()?)? {
+        return Err("Commit or stash your changes before splitting a commit".into());
+    }
+
+    let target = select_commit(&repo, opts.rev.as_deref())?;
+    let parent = target.parent()?.ok_or("Unable to split the root commit")?;
+
+    guard::check_rewrite(&repo, target.id(), &opts.force)?;
+
+    let commits = repo.commits_since(&target)?;
+    let target_tree = target.find_tree()?;
+    let message = target.message()?.trim().to_string();
+    let config = repo.config()?;
+    let committer = config.user.signature()?;
+    let mut current = parent;
+    let mut created = 0;
+
+    // The new commits are only written as objects, the branch is moved once they're all created
+    // so cancelling at any point leaves it untouched
+    loop {
+        let remaining = changed_files(&repo, &current.find_tree()?, &target_tree)?;
+
+        if remaining.is_empty() {
+            break;
+        }
+
+        let selected = match remaining.len() {
+            1 => remaining,
+            _ => {
+                let items = remaining
+                    .iter()
+                    .map(|(path, _)| path.display().to_string())
+                    .collect::<Vec<_>>();
+                let selected = select::multi(&items, None)?;
+
+                remaining
+                    .into_iter()
+                    .zip(items)
+                    .filter(|(_, item)| selected.contains(item))
+                    .map(|(change, _)| change)
+                    .collect()
+            }
+        };
+
+        if selected.is_empty() {
+            return Err("No files selected, nothing was changed".into());
+        }
+
+        let message = term::edit(
+            &repo.path().join("COMMIT_EDITMSG"),
+            &format!(
+                "{message}\n\n# Enter the message for the commit with:\n{}",
+                selected
+                    .iter()
+                    .map(|(path, _)| format!("#   {}\n", path.display()))
+                    .collect::<String>()
+            ),
+            config.core.editor.as_deref(),
+        )?;
+
+        if message.is_empty() {
+            return Err("Empty commit message, nothing was changed".into());
+        }
+
+        let entries = selected
+            .iter()
+            .map(|(path, entry)| (path.as_path(), *entry))
+            .collect::<Vec<_>>();
+        let tree = repo.update_entries(&current.find_tree()?, &entries)?;
+        let oid = repo.write_commit(
+            &config,
+            &target.author(),
+            &committer,
+            &format!("{message}\n"),
+            &tree,
+            &[&current.0],
+            opts.sign.mode(),
+        )?;
+
+        ui.renderln(&block!(
+            icon!(Check).with_status(Status::Success),
+            spacer!(),
+            Node::Attribute(Attribute::CommitShort(ObjectId::try_from(oid.as_bytes())?)),
+            spacer!(),
+            Node::text_fill(message.lines().next().unwrap_or_default())
+        ))?;
+
+        current = repo.find_commit(oid)?;
+        created += 1;
+    }
+
+    // The commits after the split one are replayed on top of the new commits
+    let steps = commits
+        .into_iter()
+        .skip(1)
+        .map(Step::Pick)
+        .collect::<Vec<_>>();
+    let new_head: Oid = repo
+        .replay(Some(current.id()), &steps, opts.sign.mode())?
+        .unwrap_or(current.id());
+
+    common::backup(&repo, &format!("split {}", target.id()))?;
+
+    repo.checkout_tree(&repo.find_commit(new_head)?.find_tree()?, false)?;
+    repo.move_head(new_head, &format!("split: {}", target.title()))?;
+
+    ui.renderln(&message_with_icon(
+        Icon::Check,
+        format!(
+            "Split {} into {created} commit{}",
+            common::commit_id(&target)?.to_hex_with_len(7),
+            if created == 1 { "" } else { "s" }
+        ),
+    ))?;
+
+    Ok(())
+}
@@ -3,18 +3,22 @@ use std::error::Error;
 use clap::Parser;
 
 use crate::{
+    cmd::commit::SignOpts,
     git::Repo,
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
     },
 };
 
 #[derive(Parser)]
 #[clap(about = "Stash the changes in a dirty working directory away")]
-pub struct Opts {}
+pub struct Opts {
+    #[clap(flatten)]
+    sign: SignOpts,
+}
 
-pub fn run(mut repo: Repo, _opts: Opts) -> Result<(), Box<dyn Error>> {
+pub fn run(mut repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
     let message = {
         let head = repo.head()?;
         let commit = head.find_commit()?;
@@ -23,9 +27,9 @@ pub fn run(mut repo: Repo, _opts: Opts) -> Result<(), Box<dyn Error>> {
         format!("{} {message}", commit.id())
     };
 
-    repo.save_stash(&message)?;
+    repo.save_stash(&message, opts.sign.mode())?;
 
-    let mut ui = TermRenderer::default();
+    let mut ui = Renderer::default();
     ui.render(&message_with_icon(Icon::Check, "Changes stashed"))?;
 
     Ok(())
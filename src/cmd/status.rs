@@ -11,14 +11,16 @@ use gix::{
     Repository,
 };
 use minus::Pager;
+use serde::Serialize;
 use tracing::instrument;
 
 use crate::{
+    git::{self, Config},
     graph::Graph,
     rebase::{Rebase, RebaseOperationType},
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{self, Format, Render, Renderer},
     },
 };
 
@@ -27,6 +29,115 @@ use crate::{
 pub struct Opts {
     #[clap(long, help = "Disable the pager")]
     no_pager: bool,
+
+    #[clap(long, conflicts_with = "porcelain", help = "Output status as JSON")]
+    json: bool,
+
+    #[clap(long, help = "Output status in a stable line format")]
+    porcelain: bool,
+}
+
+#[derive(Serialize)]
+struct Change {
+    path: String,
+    staged: bool,
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct Report {
+    branch: Option<String>,
+    head: Option<String>,
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    state: Option<&'static str>,
+    changes: Vec<Change>,
+}
+
+impl Report {
+    fn from_repo(repo: &Repository) -> Result<Self, Box<dyn Error>> {
+        let (upstream, ahead, behind) = match find_state(repo)? {
+            Some((local, remote, upstream)) => {
+                let graph = Graph::ahead_behind(repo, local, remote)?;
+                (Some(upstream), graph.ahead.len(), graph.behind.len())
+            }
+            None => (None, 0, 0),
+        };
+
+        Ok(Self {
+            branch: local_branch_name(&repo.head()?),
+            head: repo.head_id().ok().map(|id| id.to_string()),
+            upstream,
+            ahead,
+            behind,
+            state: repo.state().map(state_name),
+            changes: status_items(repo)?
+                .iter()
+                .map(|item| Change {
+                    path: item.location().to_string(),
+                    staged: matches!(item, Item::TreeIndex(_)),
+                    kind: indicator(item).name(),
+                })
+                .collect(),
+        })
+    }
+
+    fn porcelain(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "# branch.head {}",
+                self.branch.as_deref().unwrap_or("(detached)")
+            ),
+            format!(
+                "# branch.oid {}",
+                self.head.as_deref().unwrap_or("(initial)")
+            ),
+        ];
+
+        if let Some(upstream) = &self.upstream {
+            lines.push(format!("# branch.upstream {upstream}"));
+            lines.push(format!("# branch.ab +{} -{}", self.ahead, self.behind));
+        }
+
+        if let Some(state) = self.state {
+            lines.push(format!("# state {state}"));
+        }
+
+        for change in self.changes.iter() {
+            lines.push(format!(
+                "{} {} {}",
+                if change.staged { "staged" } else { "unstaged" },
+                change.kind,
+                change.path
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+fn local_branch_name(head: &gix::Head<'_>) -> Option<String> {
+    head.referent_name()
+        .and_then(|name| name.category_and_short_name())
+        .and_then(|(category, short_name)| {
+            if category == Category::LocalBranch {
+                Some(short_name.to_string())
+            } else {
+                None
+            }
+        })
+}
+
+fn state_name(state: InProgress) -> &'static str {
+    match state {
+        InProgress::ApplyMailbox | InProgress::ApplyMailboxRebase => "apply-mailbox",
+        InProgress::Bisect => "bisect",
+        InProgress::CherryPick | InProgress::CherryPickSequence => "cherry-pick",
+        InProgress::Merge => "merge",
+        InProgress::Rebase | InProgress::RebaseInteractive => "rebase",
+        InProgress::Revert | InProgress::RevertSequence => "revert",
+    }
 }
 
 #[instrument(skip(ui, repo, graph), ret(Debug))]
@@ -40,16 +151,7 @@ fn render_branch(
     match &head.kind {
         gix::head::Kind::Symbolic(_) => {
             let mut group = vec![];
-            let branch = head
-                .referent_name()
-                .and_then(|name| name.category_and_short_name())
-                .and_then(|(category, short_name)| {
-                    if category == Category::LocalBranch {
-                        Some(short_name.to_string())
-                    } else {
-                        None
-                    }
-                });
+            let branch = local_branch_name(&head);
             let object = head.into_peeled_object()?;
 
             group.push(Node::Attribute(match branch {
@@ -165,6 +267,55 @@ fn render_rebase(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn
     Ok(())
 }
 
+// Shows the commit that's being applied, `head` is the file in the git dir that holds its id
+#[instrument(skip(ui, repo), ret(Debug))]
+fn render_picking(
+    ui: &mut impl Render,
+    repo: &Repository,
+    name: &'static str,
+    head: &str,
+    command: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut children = vec![];
+
+    if let Ok(content) = std::fs::read_to_string(repo.git_dir().join(head)) {
+        let id = gix::ObjectId::from_hex(content.trim().as_bytes())?;
+        let commit = repo.find_commit(id)?;
+
+        children.push(block!(
+            spacer!(),
+            spacer!(),
+            Node::Attribute(Attribute::CommitShort(id)),
+            spacer!(),
+            Node::text_fill(
+                commit
+                    .message()?
+                    .title
+                    .to_string()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+            )
+        ));
+    }
+
+    children.push(block!(
+        spacer!(),
+        spacer!(),
+        continued!(text!(format!(
+            "Fix conflicts and run 'src {command} --continue'"
+        )))
+    ));
+
+    ui.renderln(&Node::Group(
+        name.into(),
+        None,
+        Box::new(Node::MultiLine(children)),
+    ))?;
+
+    Ok(())
+}
+
 #[instrument(skip(ui, repo), ret(Debug))]
 fn render_state(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn Error>> {
     match repo.state() {
@@ -175,8 +326,7 @@ fn render_state(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn E
                 Ok(())
             }
             InProgress::CherryPick | InProgress::CherryPickSequence => {
-                ui.renderln(&text!("Cherry-pick in progress"))?;
-                Ok(())
+                render_picking(ui, repo, "Cherry-pick", "CHERRY_PICK_HEAD", "cherry-pick")
             }
             InProgress::Merge => {
                 ui.renderln(&text!("Merge in progress"))?;
@@ -184,8 +334,7 @@ fn render_state(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn E
             }
             InProgress::Rebase | InProgress::RebaseInteractive => render_rebase(ui, repo),
             InProgress::Revert | InProgress::RevertSequence => {
-                ui.renderln(&text!("Revert in progress"))?;
-                Ok(())
+                render_picking(ui, repo, "Revert", "REVERT_HEAD", "revert")
             }
         },
         _ => Ok(()),
@@ -195,6 +344,7 @@ fn render_state(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn E
 #[instrument(skip(ui, graph), ret(Debug))]
 fn render_commits(ui: &mut impl Render, graph: Graph) -> Result<(), Box<dyn Error>> {
     let mut children = vec![];
+    let config = Config::open_default()?;
     let groups = [
         ("Unmerged into remote", graph.ahead),
         ("Unpulled from remote", graph.behind),
@@ -214,16 +364,27 @@ fn render_commits(ui: &mut impl Render, graph: Graph) -> Result<(), Box<dyn Erro
             let commit = info.object()?;
             let id = commit.id().to_string();
 
+            let verification = match commit.signature()? {
+                Some((signature, payload)) => {
+                    git::verify(&config, &signature, &payload.to_bstring())?
+                }
+                None => git::Verification::Unsigned,
+            };
+
             lines.push(block!(
-                if commit.signature()?.is_some() {
-                    icon!(Lock).with_status(Status::Success)
-                } else {
-                    spacer!()
-                },
+                verification.icon(),
                 spacer!(),
                 dimmed!(text!(id[..6].to_string())),
                 spacer!(),
-                Node::text_head_1(commit.message()?.title.to_string())
+                Node::text_fill(
+                    commit
+                        .message()?
+                        .title
+                        .to_string()
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                )
             ));
         }
 
@@ -241,14 +402,40 @@ fn render_commits(ui: &mut impl Render, graph: Graph) -> Result<(), Box<dyn Erro
     Ok(ui.renderln(&Node::MultiLine(children))?)
 }
 
-#[instrument(skip(ui, repo), ret(Debug))]
-fn render_changes(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn Error>> {
-    let mut children = vec![];
+fn indicator(item: &Item) -> Indicator {
+    match item {
+        Item::IndexWorktree(item) => match item {
+            index_worktree::Item::Modification { status, .. } => match status {
+                EntryStatus::Conflict(_) => Indicator::Conflict,
+                _ => Indicator::Modified,
+            },
+            index_worktree::Item::DirectoryContents { entry, .. } => match entry.status {
+                gix::dir::entry::Status::Untracked => Indicator::New,
+                _ => Indicator::Modified,
+            },
+            index_worktree::Item::Rewrite { .. } => Indicator::Renamed,
+        },
+        Item::TreeIndex(change) => match change {
+            gix::diff::index::ChangeRef::Addition { .. } => Indicator::New,
+            gix::diff::index::ChangeRef::Deletion { .. } => Indicator::Deleted,
+            gix::diff::index::ChangeRef::Modification { .. } => Indicator::Modified,
+            gix::diff::index::ChangeRef::Rewrite { .. } => Indicator::Renamed,
+        },
+    }
+}
+
+fn status_items(repo: &Repository) -> Result<Vec<Item>, Box<dyn Error>> {
     let status = repo
         .status(progress::Discard)?
         .untracked_files(UntrackedFiles::Files);
-    let entries = status.into_iter([])?.collect::<Result<Vec<_>, _>>()?;
-    let (staged, unstaged): (Vec<_>, Vec<_>) = entries
+
+    Ok(status.into_iter([])?.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[instrument(skip(ui, repo), ret(Debug))]
+fn render_changes(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn Error>> {
+    let mut children = vec![];
+    let (staged, unstaged): (Vec<_>, Vec<_>) = status_items(repo)?
         .into_iter()
         .partition(|e| matches!(e, Item::TreeIndex(_)));
     let groups = [("Staged Changes", staged), ("Unstaged Changes", unstaged)];
@@ -262,30 +449,10 @@ fn render_changes(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn
         let mut lines = vec![];
 
         for item in items {
-            let indicator = match &item {
-                Item::IndexWorktree(item) => match item {
-                    index_worktree::Item::Modification { status, .. } => match status {
-                        EntryStatus::Conflict(_) => Indicator::Conflict,
-                        _ => Indicator::Modified,
-                    },
-                    index_worktree::Item::DirectoryContents { entry, .. } => match entry.status {
-                        gix::dir::entry::Status::Untracked => Indicator::New,
-                        _ => Indicator::Modified,
-                    },
-                    index_worktree::Item::Rewrite { .. } => Indicator::Renamed,
-                },
-                Item::TreeIndex(change) => match change {
-                    gix::diff::index::ChangeRef::Addition { .. } => Indicator::New,
-                    gix::diff::index::ChangeRef::Deletion { .. } => Indicator::Deleted,
-                    gix::diff::index::ChangeRef::Modification { .. } => Indicator::Modified,
-                    gix::diff::index::ChangeRef::Rewrite { .. } => Indicator::Renamed,
-                },
-            };
-
             lines.push(block!(
                 spacer!(),
                 spacer!(),
-                Node::Indicator(indicator),
+                Node::Indicator(indicator(&item)),
                 spacer!(),
                 text!(item.location().to_string())
             ));
@@ -306,7 +473,9 @@ fn render_changes(ui: &mut impl Render, repo: &Repository) -> Result<(), Box<dyn
 }
 
 #[instrument(skip(repo), ret(Debug))]
-fn find_state(repo: &Repository) -> Result<Option<(gix::Id<'_>, gix::Id<'_>)>, Box<dyn Error>> {
+fn find_state(
+    repo: &Repository,
+) -> Result<Option<(gix::Id<'_>, gix::Id<'_>, String)>, Box<dyn Error>> {
     let Some(local) = repo.head_ref()? else {
         return Ok(None);
     };
@@ -322,15 +491,16 @@ fn find_state(repo: &Repository) -> Result<Option<(gix::Id<'_>, gix::Id<'_>)>, B
         return Ok(None);
     };
 
+    let name = upstream.shorten().to_string();
     let upstream = repo.find_reference(upstream.as_partial_name())?;
 
-    Ok(Some((local.id(), upstream.id())))
+    Ok(Some((local.id(), upstream.id(), name)))
 }
 
 #[instrument(skip(ui, repo), ret(Debug))]
 fn render(mut ui: impl Render, repo: Repository) -> Result<(), Box<dyn Error>> {
     let graph = match find_state(&repo)? {
-        Some((local, remote)) => Some(Graph::ahead_behind(&repo, local, remote)?),
+        Some((local, remote, _)) => Some(Graph::ahead_behind(&repo, local, remote)?),
         None => None,
     };
 
@@ -349,13 +519,25 @@ fn render(mut ui: impl Render, repo: Repository) -> Result<(), Box<dyn Error>> {
 }
 
 pub fn run(repo: Repository, opts: Opts) -> Result<(), Box<dyn Error>> {
-    if opts.no_pager {
-        render(TermRenderer::default(), repo)
+    if opts.json || opts.porcelain {
+        let report = Report::from_repo(&repo)?;
+
+        if opts.json {
+            println!("{}", serde_json::to_string(&report)?);
+        } else {
+            println!("{}", report.porcelain());
+        }
+
+        return Ok(());
+    }
+
+    if opts.no_pager || render::format() == Format::Json {
+        render(Renderer::default(), repo)
     } else {
         let mut pager = Pager::new();
         pager.set_prompt("status, q to quit")?;
 
-        render(TermRenderer::new(&mut pager), repo)?;
+        render(Renderer::new(&mut pager), repo)?;
         minus::page_all(pager)?;
 
         Ok(())
@@ -0,0 +1,51 @@
+use std::error::Error;
+
+use clap::Parser;
+use gix::ObjectId;
+
+use crate::{
+    cmd::commit::SignOpts,
+    git::Repo,
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Create an annotated tag")]
+pub struct Opts {
+    #[clap(short, long, help = "Tag message (defaults to the tag name)")]
+    message: Option<String>,
+
+    #[clap(flatten)]
+    sign: SignOpts,
+
+    #[clap(help = "Tag name")]
+    name: String,
+
+    #[clap(default_value = "HEAD", help = "Commit to tag")]
+    rev: String,
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let commit = repo
+        .resolve_commit(&opts.rev)?
+        .ok_or("Revision not found")?;
+    let message = opts.message.as_deref().unwrap_or(&opts.name);
+
+    repo.create_tag(&opts.name, &commit, message, opts.sign.mode())?;
+
+    let mut ui = Renderer::default();
+    ui.renderln(&continued!(block!(
+        text!("Created"),
+        spacer!(),
+        Node::Attribute(Attribute::Tag(opts.name.into())),
+        spacer!(),
+        dimmed!(Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+            commit.id().as_bytes()
+        )?)))
+    )))?;
+
+    Ok(())
+}
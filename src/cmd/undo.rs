@@ -0,0 +1,97 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::{
+    cmd::{common, list::reflog_entry},
+    git::{
+        reflog::{self, Entry, Operation},
+        Repo,
+    },
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+pub struct Opts {
+    #[clap(long, help = "Only show the operation without restoring anything")]
+    dry_run: bool,
+}
+
+// Commits keep their changes staged when undone, everything else restores the worktree too
+fn restore(repo: &Repo, entry: &Entry, undo: bool) -> Result<(), Box<dyn Error>> {
+    let (target, verb) = match undo {
+        true => (entry.old, "undo"),
+        false => (entry.new, "redo"),
+    };
+    let message = format!("{verb}: {}", entry.message);
+
+    if target.is_zero() {
+        return Err("Nothing to restore before the first commit".into());
+    }
+
+    match entry.operation() {
+        Operation::Checkout => {
+            let (from, to) = entry.checkout_names().ok_or("Unknown checkout entry")?;
+            let name = if undo { from } else { to };
+
+            match repo.find_branch(name) {
+                Ok(branch) => repo.checkout(&branch.into_ref())?,
+                Err(_) => repo.checkout_detached(target)?,
+            }
+
+            repo.reword_reflog(&message)?;
+        }
+        Operation::Commit | Operation::Amend | Operation::CherryPick | Operation::Revert => {
+            repo.head()?.set_target(target, &message)?;
+        }
+        _ => {
+            repo.checkout_tree(&repo.find_commit(target)?.find_tree()?, false)?;
+            repo.head()?.set_target(target, &message)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn apply(repo: Repo, opts: Opts, undo: bool) -> Result<(), Box<dyn Error>> {
+    let mut ui = Renderer::default();
+    let entries = reflog::group(repo.reflog()?.into_iter().rev());
+    let (done, undone) = reflog::replay(&entries);
+    let entry = match undo {
+        true => done.last().ok_or("Nothing to undo")?,
+        false => undone.last().ok_or("Nothing to redo")?,
+    };
+
+    if !opts.dry_run {
+        common::backup(&repo, if undo { "undo" } else { "redo" })?;
+        restore(&repo, entry, undo)?;
+    }
+
+    let verb = match (opts.dry_run, undo) {
+        (true, true) => "Would undo",
+        (true, false) => "Would redo",
+        (false, true) => "Undone",
+        (false, false) => "Redone",
+    };
+
+    ui.renderln(&block!(
+        icon!(Check).with_status(Status::Success),
+        spacer!(),
+        text!(verb),
+        spacer!(),
+        reflog_entry(entry)?
+    ))?;
+
+    Ok(())
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    apply(repo, opts, true)
+}
+
+pub fn redo(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    apply(repo, opts, false)
+}
@@ -6,21 +6,54 @@ use crate::{
     git::Repo,
     term::{
         node::prelude::*,
-        render::{Render, TermRenderer},
+        render::{Render, Renderer},
+        select,
     },
 };
 
 #[derive(Parser)]
-#[clap(about = "Apply the changes from the last stash")]
+#[clap(about = "Apply the changes from a stash")]
 pub struct Opts {
-    #[clap(short = 'n', long, default_value = "0")]
-    index: usize,
+    #[clap(short = 'n', long, help = "Stash index (prompts when omitted)")]
+    index: Option<usize>,
+}
+
+fn select_stash(repo: &Repo) -> Result<Option<usize>, Box<dyn Error>> {
+    let items = repo
+        .stashes()?
+        .enumerate()
+        .map(|(i, commit)| {
+            commit.map(|commit| {
+                let message = commit.message().unwrap_or_default();
+                format!(
+                    "stash@{{{i}}} {}",
+                    message.lines().next().unwrap_or_default()
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if items.is_empty() {
+        return Err("No stashes found".into());
+    }
+
+    let selected = select::single(&items, Some("src diff --no-pager {1}".to_string()))?;
+
+    Ok(selected.and_then(|selected| items.iter().position(|item| *item == selected)))
 }
 
 pub fn run(mut repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
-    repo.pop_stash(opts.index)?;
+    let index = match opts.index {
+        Some(index) => index,
+        None => match select_stash(&repo)? {
+            Some(index) => index,
+            None => return Err("No stash selected".into()),
+        },
+    };
+
+    repo.pop_stash(index)?;
 
-    let mut term = TermRenderer::default();
+    let mut term = Renderer::default();
     term.render(&message_with_icon(Icon::Check, "Changes applied"))?;
 
     Ok(())
@@ -0,0 +1,40 @@
+use std::error::Error;
+
+use clap::Parser;
+use gix::ObjectId;
+
+use crate::{
+    git::Repo,
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Parser)]
+#[clap(about = "Verify the signature of a commit")]
+pub struct Opts {
+    #[clap(default_value = "HEAD", help = "Commit to verify")]
+    rev: String,
+}
+
+pub fn run(repo: Repo, opts: Opts) -> Result<(), Box<dyn Error>> {
+    let config = repo.config()?;
+    let commit = repo
+        .resolve_commit(&opts.rev)?
+        .ok_or("Revision not found")?;
+    let verification = repo.verify_commit(&config, &commit)?;
+
+    let mut ui = Renderer::default();
+    ui.renderln(&block!(
+        verification.icon(),
+        spacer!(),
+        Node::Attribute(Attribute::CommitShort(ObjectId::try_from(
+            commit.id().as_bytes()
+        )?)),
+        spacer!(),
+        text!(verification.description())
+    ))?;
+
+    Ok(())
+}
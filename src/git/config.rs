@@ -10,9 +10,15 @@ pub enum Error {
     Utf8(#[from] std::str::Utf8Error),
     #[error("invalid gpg format: {0}")]
     InvalidGpgFormat(String),
+    #[error("invalid value for src.protectPushed: {0} (expected refuse, warn or off)")]
+    InvalidProtection(String),
 }
 
+#[derive(Default)]
 pub enum GpgFormat {
+    #[default]
+    OpenPgp,
+    X509,
     Ssh,
 }
 
@@ -20,13 +26,20 @@ pub struct Commit {
     pub gpg_sign: bool,
 }
 
+pub struct Tag {
+    pub gpg_sign: bool,
+}
+
 #[derive(Default)]
 pub struct GpgConfig {
     pub program: Option<String>,
+    pub allowed_signers_file: Option<String>,
+    pub default_key_command: Option<String>,
 }
 
 pub struct Gpg {
-    pub format: Option<GpgFormat>,
+    pub format: GpgFormat,
+    pub program: Option<String>,
     pub config: HashMap<String, GpgConfig>,
 }
 
@@ -46,11 +59,42 @@ pub struct Push {
     pub auto_setup_remote: bool,
 }
 
+pub struct Blame {
+    pub ignore_revs_file: Option<String>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    #[default]
+    Refuse,
+    Warn,
+    Off,
+}
+
+pub struct Rewrite {
+    // What to do when rewriting commits that are already on the upstream
+    pub protect_pushed: Protection,
+}
+
+pub struct Core {
+    pub editor: Option<String>,
+}
+
+pub struct Backup {
+    // Backups older than this are pruned when a new one is made
+    pub max_age_days: i64,
+}
+
 pub struct Config {
+    pub core: Core,
     pub commit: Commit,
+    pub tag: Tag,
     pub gpg: Gpg,
     pub user: User,
     pub push: Push,
+    pub blame: Blame,
+    pub backup: Backup,
+    pub rewrite: Rewrite,
 }
 
 impl Config {
@@ -82,8 +126,11 @@ fn parse_gpg_config(config: &git2::Config) -> Result<HashMap<String, GpgConfig>,
 
         let value: &mut GpgConfig = gpg.entry(components[1].to_string()).or_default();
 
-        if components[2] == "program" {
-            value.program = string(config, name)?
+        match components[2] {
+            "program" => value.program = string(config, name)?,
+            "allowedsignersfile" => value.allowed_signers_file = string(config, name)?,
+            "defaultkeycommand" => value.default_key_command = string(config, name)?,
+            _ => {}
         }
     }
 
@@ -98,15 +145,25 @@ impl TryFrom<git2::Config> for Config {
             gpg: Gpg {
                 format: string(&config, "gpg.format")?
                     .map(|format| match format.as_str() {
+                        "openpgp" => Ok(GpgFormat::OpenPgp),
+                        "x509" => Ok(GpgFormat::X509),
                         "ssh" => Ok(GpgFormat::Ssh),
                         format => Err(Error::InvalidGpgFormat(format.to_string())),
                     })
-                    .transpose()?,
+                    .transpose()?
+                    .unwrap_or_default(),
+                program: string(&config, "gpg.program")?,
                 config: parse_gpg_config(&config)?,
             },
+            core: Core {
+                editor: string(&config, "core.editor")?,
+            },
             commit: Commit {
                 gpg_sign: bool_or_default(&config, "commit.gpgsign")?,
             },
+            tag: Tag {
+                gpg_sign: bool_or_default(&config, "tag.gpgsign")?,
+            },
             user: User {
                 name: string(&config, "user.name")?,
                 email: config.get_string("user.email")?,
@@ -115,6 +172,26 @@ impl TryFrom<git2::Config> for Config {
             push: Push {
                 auto_setup_remote: bool_or_default(&config, "push.autoSetupRemote")?,
             },
+            blame: Blame {
+                ignore_revs_file: string(&config, "blame.ignoreRevsFile")?,
+            },
+            rewrite: Rewrite {
+                protect_pushed: string(&config, "src.protectPushed")?
+                    .map(|value| match value.as_str() {
+                        "refuse" => Ok(Protection::Refuse),
+                        "warn" => Ok(Protection::Warn),
+                        "off" => Ok(Protection::Off),
+                        value => Err(Error::InvalidProtection(value.to_string())),
+                    })
+                    .transpose()?
+                    .unwrap_or_default(),
+            },
+            backup: Backup {
+                max_age_days: config
+                    .get_i64("src.backup.maxAge")
+                    .optional()?
+                    .unwrap_or(30),
+            },
         })
     }
 }
@@ -0,0 +1,141 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
+use regex::Regex;
+
+use super::Commit;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid date: {0} (expected YYYY-MM-DD, RFC 3339 or '<n> <unit>s ago')")]
+    InvalidDate(String),
+}
+
+#[derive(Default)]
+pub struct CommitFilter {
+    pub author: Option<Regex>,
+    pub grep: Option<Regex>,
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+    pub limit: Option<usize>,
+    // `Some(true)` only keeps merge commits, `Some(false)` leaves them out
+    pub merges: Option<bool>,
+    pub first_parent: bool,
+    // A single path is followed across renames
+    pub paths: Vec<PathBuf>,
+}
+
+impl CommitFilter {
+    pub fn follow(&self) -> bool {
+        self.paths.len() == 1
+    }
+
+    // Only checks the commit metadata, paths require a diff and are checked by the walk
+    pub fn matches(&self, commit: &Commit<'_>) -> bool {
+        let time = commit.time();
+
+        if self.until.is_some_and(|until| time > until) {
+            return false;
+        }
+
+        if self.since.is_some_and(|since| time < since) {
+            return false;
+        }
+
+        if let Some(merges) = self.merges {
+            if (commit.0.parent_count() > 1) != merges {
+                return false;
+            }
+        }
+
+        if let Some(author) = &self.author {
+            if !author.is_match(&commit.author().to_string()) {
+                return false;
+            }
+        }
+
+        if let Some(grep) = &self.grep {
+            if !grep.is_match(commit.message().unwrap_or_default()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn parse_relative(input: &str) -> Option<Duration> {
+    let mut parts = input.split_whitespace();
+    let amount = parts.next()?.parse::<i64>().ok()?;
+    let unit = parts.next()?;
+
+    if !matches!(parts.next(), None | Some("ago")) || parts.next().is_some() {
+        return None;
+    }
+
+    Some(match unit.trim_end_matches('s') {
+        "second" => Duration::seconds(amount),
+        "minute" => Duration::minutes(amount),
+        "hour" => Duration::hours(amount),
+        "day" => Duration::days(amount),
+        "week" => Duration::weeks(amount),
+        "month" => Duration::days(amount * 30),
+        "year" => Duration::days(amount * 365),
+        _ => return None,
+    })
+}
+
+pub fn parse_date(input: &str) -> Result<DateTime<Local>, Error> {
+    let input = input.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Ok(date.with_timezone(&Local));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        if let Some(date) = date
+            .and_time(NaiveTime::MIN)
+            .and_local_timezone(Local)
+            .earliest()
+        {
+            return Ok(date);
+        }
+    }
+
+    match input {
+        "now" => Ok(Local::now()),
+        "yesterday" => Ok(Local::now() - Duration::days(1)),
+        input => parse_relative(input)
+            .map(|duration| Local::now() - duration)
+            .ok_or_else(|| Error::InvalidDate(input.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_relative() {
+        assert_eq!(parse_relative("2 weeks ago"), Some(Duration::weeks(2)));
+        assert_eq!(parse_relative("1 day"), Some(Duration::days(1)));
+        assert_eq!(parse_relative("3 hours ago"), Some(Duration::hours(3)));
+        assert_eq!(parse_relative("3 fortnights ago"), None);
+        assert_eq!(parse_relative("a week ago"), None);
+        assert_eq!(parse_relative("1 week ago today"), None);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let date = parse_date("2024-03-01").unwrap();
+        assert_eq!(
+            date.format("%Y-%m-%d %H:%M").to_string(),
+            "2024-03-01 00:00"
+        );
+
+        let date = parse_date("2024-03-01T12:00:00Z").unwrap();
+        assert_eq!(date.timestamp(), 1709294400);
+
+        assert!(parse_date("last tuesday").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use git2::{IndexAddOption, IntoCString};
 
@@ -31,6 +31,28 @@ impl Index {
         Ok(count)
     }
 
+    // Files in the index (submodules are left out) with the blob id of their staged content
+    pub fn files(&self) -> Vec<(PathBuf, git2::Oid)> {
+        self.0
+            .iter()
+            .filter(|entry| entry.mode & 0o170000 != 0o160000)
+            .map(|entry| {
+                (
+                    PathBuf::from(String::from_utf8_lossy(&entry.path).into_owned()),
+                    entry.id,
+                )
+            })
+            .collect()
+    }
+
+    // Stages the worktree content of the paths, including deletions
+    pub fn stage(&mut self, paths: &[PathBuf]) -> Result<(), git2::Error> {
+        let paths = || paths.iter().map(PathBuf::as_path);
+
+        self.0.add_all(paths(), IndexAddOption::DEFAULT, None)?;
+        self.0.update_all(paths(), None)
+    }
+
     pub fn write(&mut self) -> Result<(), git2::Error> {
         self.0.write()
     }
@@ -2,19 +2,24 @@ use chrono::{DateTime, Local, TimeZone};
 use git2::{Error, ErrorClass, ErrorCode};
 
 mod config;
+mod filter;
 mod index;
 mod objects;
+pub mod reflog;
 mod remote;
 mod repo;
 mod resolve;
+pub mod rewrite;
 mod signer;
 mod status;
 
-pub use config::Config;
+pub use config::{Config, Protection};
+pub use filter::{parse_date, CommitFilter};
 pub use objects::*;
 pub use remote::{ProgressEvent, RemoteOpts, SidebandOp};
-pub use repo::{CheckoutError, DiffOpts, Repo};
+pub use repo::{Applied, Backup, CheckoutError, DiffOpts, Repo};
 pub use resolve::Pattern;
+pub use signer::{verify, SignMode, Verification};
 
 pub trait Optional<T> {
     fn optional(self) -> Result<Option<T>, Error>;
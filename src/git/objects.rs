@@ -1,4 +1,7 @@
-use std::str::Utf8Error;
+use std::{
+    path::{Path, PathBuf},
+    str::Utf8Error,
+};
 
 use chrono::{DateTime, Local};
 use git2::Signature;
@@ -23,6 +26,23 @@ impl<'a> From<git2::Tree<'a>> for Tree<'a> {
     }
 }
 
+impl Tree<'_> {
+    pub fn files(&self) -> Result<Vec<(PathBuf, git2::Oid)>, git2::Error> {
+        let mut files = vec![];
+
+        self.0.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                let name = String::from_utf8_lossy(entry.name_bytes());
+                files.push((Path::new(root).join(name.as_ref()), entry.id()));
+            }
+
+            git2::TreeWalkResult::Ok
+        })?;
+
+        Ok(files)
+    }
+}
+
 #[allow(dead_code)]
 pub struct Object<'a>(pub git2::Object<'a>);
 
@@ -122,6 +142,15 @@ impl<'a> Commit<'a> {
         std::str::from_utf8(self.0.message_bytes())
     }
 
+    // The first line of the message
+    pub fn title(&self) -> &str {
+        self.message()
+            .unwrap_or_default()
+            .lines()
+            .next()
+            .unwrap_or_default()
+    }
+
     pub fn message_formatted(&self) -> String {
         self.message()
             .map(|msg| {
@@ -137,6 +166,10 @@ impl<'a> Commit<'a> {
         self.parent_n(1)
     }
 
+    pub fn parent_ids(&self) -> Vec<git2::Oid> {
+        self.0.parent_ids().collect()
+    }
+
     pub fn parent_n(&self, n: usize) -> Result<Option<Commit<'a>>, git2::Error> {
         if self.0.parent_count() == 0 {
             return Ok(None);
@@ -0,0 +1,249 @@
+use chrono::{DateTime, Local};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Commit,
+    Amend,
+    Pull,
+    Checkout,
+    CherryPick,
+    Revert,
+    Reset,
+    Rebase,
+    Undo,
+    Redo,
+    Other,
+}
+
+impl Operation {
+    // Recognizes the reflog messages written by src and by git itself
+    pub fn from_message(message: &str) -> Self {
+        let kind = message.split(':').next().unwrap_or_default();
+
+        match kind {
+            "commit amended" | "commit (amend)" => Operation::Amend,
+            "commit" | "commit (initial)" | "commit (merge)" => Operation::Commit,
+            "fast-forward" | "pull" => Operation::Pull,
+            _ if kind.starts_with("pull ") => Operation::Pull,
+            "checkout" => Operation::Checkout,
+            "cherry-pick" => Operation::CherryPick,
+            "revert" => Operation::Revert,
+            "reset" => Operation::Reset,
+            _ if kind.starts_with("rebase") => Operation::Rebase,
+            "undo" => Operation::Undo,
+            "redo" => Operation::Redo,
+            _ => Operation::Other,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Commit => "commit",
+            Operation::Amend => "amend",
+            Operation::Pull => "pull",
+            Operation::Checkout => "checkout",
+            Operation::CherryPick => "cherry-pick",
+            Operation::Revert => "revert",
+            Operation::Reset => "reset",
+            Operation::Rebase => "rebase",
+            Operation::Undo => "undo",
+            Operation::Redo => "redo",
+            Operation::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub old: git2::Oid,
+    pub new: git2::Oid,
+    pub message: String,
+    pub time: DateTime<Local>,
+}
+
+impl Entry {
+    pub fn operation(&self) -> Operation {
+        Operation::from_message(&self.message)
+    }
+
+    // The message without the operation prefix
+    pub fn summary(&self) -> &str {
+        self.message
+            .split_once(": ")
+            .map_or(self.message.as_str(), |(_, summary)| summary)
+    }
+
+    // The branches (or commits) of a "checkout: moving from <from> to <to>" entry
+    pub fn checkout_names(&self) -> Option<(&str, &str)> {
+        self.message
+            .strip_prefix("checkout: moving from ")?
+            .split_once(" to ")
+    }
+}
+
+// Collapses the entries written while rebasing (oldest first) into a single entry going from
+// the commit before the rebase to its result, so that the rebase is undone as a whole
+pub fn group(entries: impl IntoIterator<Item = Entry>) -> Vec<Entry> {
+    let mut grouped: Vec<Entry> = vec![];
+    let mut rebasing = false;
+
+    for entry in entries {
+        let rebase = entry.operation() == Operation::Rebase;
+        let finished = entry.message.contains("(finish)") || entry.message.contains("(abort)");
+
+        match grouped.last_mut() {
+            Some(last) if rebasing && rebase => {
+                last.new = entry.new;
+                last.message = entry.message;
+                last.time = entry.time;
+            }
+            _ => grouped.push(entry),
+        }
+
+        rebasing = rebase && !finished;
+    }
+
+    grouped
+}
+
+// Replays the entries (oldest first) to find the operations that can be undone and redone, the
+// last element of each is the next one to undo or redo
+pub fn replay<'a>(
+    entries: impl IntoIterator<Item = &'a Entry>,
+) -> (Vec<&'a Entry>, Vec<&'a Entry>) {
+    let (mut done, mut undone) = (vec![], vec![]);
+
+    for entry in entries {
+        match entry.operation() {
+            Operation::Undo => {
+                if let Some(entry) = done.pop() {
+                    undone.push(entry);
+                }
+            }
+            Operation::Redo => {
+                if let Some(entry) = undone.pop() {
+                    done.push(entry);
+                }
+            }
+            _ => {
+                done.push(entry);
+                undone.clear();
+            }
+        }
+    }
+
+    (done, undone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(message: &str) -> Entry {
+        moved(0, 0, message)
+    }
+
+    fn moved(old: u8, new: u8, message: &str) -> Entry {
+        Entry {
+            old: git2::Oid::from_bytes(&[old; 20]).unwrap(),
+            new: git2::Oid::from_bytes(&[new; 20]).unwrap(),
+            message: message.to_string(),
+            time: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_operation_from_message() {
+        assert_eq!(
+            Operation::from_message("commit: feat: add x"),
+            Operation::Commit
+        );
+        assert_eq!(
+            Operation::from_message("commit (initial): init"),
+            Operation::Commit
+        );
+        assert_eq!(
+            Operation::from_message("commit amended: fix"),
+            Operation::Amend
+        );
+        assert_eq!(Operation::from_message("fast-forward"), Operation::Pull);
+        assert_eq!(
+            Operation::from_message("pull --rebase: done"),
+            Operation::Pull
+        );
+        assert_eq!(
+            Operation::from_message("checkout: moving from main to dev"),
+            Operation::Checkout
+        );
+        assert_eq!(Operation::from_message("undo: commit: x"), Operation::Undo);
+        assert_eq!(
+            Operation::from_message("rebase (finish): returning to refs/heads/main"),
+            Operation::Rebase
+        );
+        assert_eq!(Operation::from_message("merge dev"), Operation::Other);
+    }
+
+    #[test]
+    fn test_checkout_names() {
+        assert_eq!(
+            entry("checkout: moving from main to feature/x").checkout_names(),
+            Some(("main", "feature/x"))
+        );
+        assert_eq!(entry("commit: x").checkout_names(), None);
+    }
+
+    #[test]
+    fn test_group() {
+        let entries = group([
+            moved(0, 1, "commit: a"),
+            moved(1, 2, "rebase (start): checkout main"),
+            moved(2, 3, "rebase (pick): b"),
+            moved(3, 4, "rebase (pick): c"),
+            moved(4, 4, "rebase (finish): returning to refs/heads/dev"),
+            moved(4, 5, "commit: d"),
+        ]);
+        let messages = entries
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            [
+                "commit: a",
+                "rebase (finish): returning to refs/heads/dev",
+                "commit: d"
+            ]
+        );
+        assert_eq!(
+            (entries[1].old, entries[1].new),
+            (entries[0].new, entries[2].old)
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let entries = [
+            entry("commit: a"),
+            entry("commit: b"),
+            entry("undo: commit: b"),
+            entry("undo: commit: a"),
+            entry("redo: commit: a"),
+        ];
+        let (done, undone) = replay(&entries);
+
+        assert_eq!(done.last().map(|e| e.message.as_str()), Some("commit: a"));
+        assert_eq!(undone.last().map(|e| e.message.as_str()), Some("commit: b"));
+
+        // A new operation can't be followed by a redo
+        let entries = [
+            entry("commit: a"),
+            entry("undo: commit: a"),
+            entry("commit: c"),
+        ];
+        let (done, undone) = replay(&entries);
+
+        assert_eq!(done.last().map(|e| e.message.as_str()), Some("commit: c"));
+        assert!(undone.is_empty());
+    }
+}
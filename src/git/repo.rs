@@ -1,17 +1,36 @@
-use std::{borrow::Cow, error::Error, path::Path};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
 
+use chrono::{DateTime, Duration, Local};
 use git2::{
-    build::CheckoutBuilder, string_array::StringArray, BranchType, DiffFindOptions, DiffOptions,
-    ErrorClass, ErrorCode, StashApplyOptions, StashFlags, StatusOptions,
+    build::{CheckoutBuilder, TreeUpdateBuilder},
+    string_array::StringArray,
+    BlameOptions, BranchType, CherrypickOptions, Delta, DiffFindOptions, DiffOptions, ErrorClass,
+    ErrorCode, FileMode, ObjectType, ResetType, RevertOptions, Sort, StashApplyOptions, StashFlags,
+    StatusOptions,
 };
+use resolve_path::PathResolveExt;
 
-use crate::git::signer::{ssh::SshSigner, Signer};
+use crate::git::{
+    signer::{self, SignMode, Verification},
+    Optional,
+};
 
 use super::{
     config::Config,
+    filter::CommitFilter,
     index::Index,
     objects::{Branch, Commit, Ref, Tree},
+    reflog,
     remote::Remote,
+    resolve::Pattern,
+    rewrite::Step,
     status::Status,
 };
 
@@ -32,12 +51,110 @@ impl From<git2::Error> for CheckoutError {
     }
 }
 
+pub enum Applied {
+    Commit(git2::Oid),
+    Empty,
+    Conflict,
+}
+
+// A snapshot of HEAD, the index and the worktree, stored as a commit whose tree is the worktree
+// and whose parents are a commit of the index tree, HEAD (when it's not unborn) and the commits
+// of other recorded refs. The ref HEAD pointed at is recorded in a "Head" trailer of the message
+// and the other refs in "Ref" trailers
+pub struct Backup<'a> {
+    pub name: String,
+    pub reason: String,
+    // `None` when HEAD was detached
+    pub head_ref: Option<String>,
+    // Refs that aren't restored but whose commits are kept, like the remote branch overwritten
+    // by a force push
+    pub refs: Vec<(String, git2::Oid)>,
+    pub time: DateTime<Local>,
+    pub commit: Commit<'a>,
+}
+
+impl<'a> Backup<'a> {
+    fn parse(name: &str, commit: Commit<'a>) -> Self {
+        let mut head_ref = None;
+        let mut refs = vec![];
+
+        let trailers = commit
+            .message()
+            .ok()
+            .and_then(|message| git2::message_trailers_strs(message).ok());
+
+        for (key, value) in trailers.iter().flat_map(|trailers| trailers.iter()) {
+            match key {
+                "Head" => head_ref = Some(value.to_string()),
+                "Ref" => {
+                    let Some((name, oid)) = value.split_once(' ') else {
+                        continue;
+                    };
+
+                    if let Ok(oid) = git2::Oid::from_str(oid) {
+                        refs.push((name.to_string(), oid));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            name: name.to_string(),
+            reason: commit.title().to_string(),
+            head_ref,
+            refs,
+            time: commit.time(),
+            commit,
+        }
+    }
+
+    pub fn head(&self) -> Option<git2::Oid> {
+        // Without HEAD the index commit is only followed by the commits of the other refs
+        if self.commit.0.parent_count() < self.refs.len() + 2 {
+            return None;
+        }
+
+        self.commit.0.parent_id(1).ok()
+    }
+}
+
+const BACKUP_REFS: &str = "refs/src/backup/";
+
+// Large untracked files are mostly build output, hashing them would slow down every backup
+const MAX_UNTRACKED_SIZE: u64 = 10 * 1024 * 1024;
+
+// The bits of the index entry flags holding the conflict stage
+const INDEX_STAGE_MASK: u16 = 0x3000;
+
 #[derive(Debug, thiserror::Error)]
 pub enum StashError {
     #[error("git error: {0}")]
     Git(#[from] git2::Error),
     #[error("config error: {0}")]
     Config(#[from] super::config::Error),
+    #[error("failed to sign stash: {0}")]
+    Sign(Box<dyn Error>),
+}
+
+fn format_signature(signature: &git2::Signature<'_>) -> String {
+    let when = signature.when();
+    let offset = when.offset_minutes().abs();
+
+    format!(
+        "{} <{}> {} {}{:02}{:02}",
+        String::from_utf8_lossy(signature.name_bytes()),
+        String::from_utf8_lossy(signature.email_bytes()),
+        when.seconds(),
+        when.sign(),
+        offset / 60,
+        offset % 60
+    )
+}
+
+fn find_similar(diff: &mut git2::Diff<'_>) -> Result<(), git2::Error> {
+    let mut find_opts = DiffFindOptions::new();
+    diff.find_similar(Some(find_opts.renames(true).copies(true)))
 }
 
 pub struct Remotes<'a> {
@@ -64,6 +181,7 @@ impl<'a> Iterator for Remotes<'a> {
 enum DiffType<'a> {
     All(&'a Tree<'a>),
     Staged(&'a Tree<'a>),
+    Trees(&'a Tree<'a>, &'a Tree<'a>),
     Unstaged,
 }
 
@@ -101,19 +219,41 @@ impl<'a> DiffOpts<'a> {
         self
     }
 
+    pub fn with_trees(mut self, old: &'a Tree<'a>, new: &'a Tree<'a>) -> Self {
+        self.ty = DiffType::Trees(old, new);
+        self
+    }
+
     pub fn with_pathspec(mut self, pathspec: &str) -> Self {
         self.diff_opts.pathspec(pathspec);
         self
     }
+
+    // Keeps whitespace changes so that the diff can be applied again
+    pub fn exact(mut self) -> Self {
+        self.diff_opts.ignore_whitespace(false);
+        self
+    }
+
+    // Hunks only cover the changed lines, so neighbouring changes aren't merged into one hunk
+    pub fn without_context(mut self) -> Self {
+        self.diff_opts.context_lines(0);
+        self
+    }
 }
 
 pub struct Repo {
     repo: git2::Repository,
+    // Verifying a signature runs an external program, so each commit is only verified once
+    verified: RefCell<HashMap<git2::Oid, Verification>>,
 }
 
 impl From<git2::Repository> for Repo {
     fn from(repo: git2::Repository) -> Self {
-        Self { repo }
+        Self {
+            repo,
+            verified: RefCell::default(),
+        }
     }
 }
 
@@ -122,6 +262,26 @@ impl Repo {
         self.repo.path()
     }
 
+    pub fn workdir(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    // Paths given by the user are relative to the current directory, but git expects them to be
+    // relative to the root of the repository
+    pub fn relative_path(&self, path: &Path) -> PathBuf {
+        let path = path.resolve().into_owned();
+
+        match self.workdir().and_then(|root| path.strip_prefix(root).ok()) {
+            Some(relative) => relative.to_path_buf(),
+            None => path,
+        }
+    }
+
+    // Includes the repository config, unlike `Config::open_default`
+    pub fn config(&self) -> Result<Config, super::config::Error> {
+        self.repo.config()?.try_into()
+    }
+
     pub fn head(&self) -> Result<Ref<'_>, git2::Error> {
         self.repo.head().map(Into::into)
     }
@@ -130,6 +290,43 @@ impl Repo {
         self.repo.find_commit(oid).map(Into::into)
     }
 
+    pub fn resolve_commit(&self, rev: &str) -> Result<Option<Commit<'_>>, git2::Error> {
+        if let Ok(("", pattern)) = Pattern::parse(rev) {
+            if let Some(oid) = pattern.resolve(self)? {
+                return self.find_commit(oid).map(Some);
+            }
+        }
+
+        match self.repo.revparse_single(rev) {
+            Ok(object) => object.peel_to_commit().map(|commit| Some(commit.into())),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn verify_commit(
+        &self,
+        config: &Config,
+        commit: &Commit<'_>,
+    ) -> Result<Verification, Box<dyn Error>> {
+        if !commit.is_signed() {
+            return Ok(Verification::Unsigned);
+        }
+
+        if let Some(verification) = self.verified.borrow().get(&commit.id()) {
+            return Ok(verification.clone());
+        }
+
+        let (signature, payload) = self.repo.extract_signature(&commit.id(), None)?;
+        let verification = signer::verify(config, &signature, &payload)?;
+
+        self.verified
+            .borrow_mut()
+            .insert(commit.id(), verification.clone());
+
+        Ok(verification)
+    }
+
     pub fn find_annotated_commit(
         &self,
         oid: git2::Oid,
@@ -185,6 +382,15 @@ impl Repo {
         Ok(())
     }
 
+    pub fn checkout_detached(&self, oid: git2::Oid) -> Result<(), CheckoutError> {
+        let tree = self.find_commit(oid)?.find_tree()?;
+
+        self.checkout_tree(&tree, false)?;
+        self.repo.set_head_detached(oid)?;
+
+        Ok(())
+    }
+
     pub fn branches(
         &self,
     ) -> Result<impl Iterator<Item = Result<Branch<'_>, git2::Error>> + '_, git2::Error> {
@@ -216,7 +422,8 @@ impl Repo {
     pub fn commits(
         &self,
         reference: &Ref<'_>,
-    ) -> Result<impl Iterator<Item = Result<Commit<'_>, git2::Error>>, git2::Error> {
+        mut filter: CommitFilter,
+    ) -> Result<impl Iterator<Item = Result<Commit<'_>, git2::Error>> + '_, git2::Error> {
         let mut walker = self.repo.revwalk()?;
         walker.push_ref(
             reference
@@ -224,22 +431,188 @@ impl Repo {
                 .map_err(|e| git2::Error::new(ErrorCode::User, ErrorClass::None, e.to_string()))?,
         )?;
 
+        if filter.first_parent {
+            walker.simplify_first_parent()?;
+        }
+
+        if filter.since.is_some() {
+            walker.set_sorting(Sort::TIME)?;
+        }
+
+        let since = filter.since;
+        let limit = filter.limit.unwrap_or(usize::MAX);
+
+        Ok(walker
+            .map(|oid| oid.and_then(|oid| self.find_commit(oid)))
+            // The walk is sorted by date, so everything after the first older commit is older too
+            .take_while(move |commit| match (commit, since) {
+                (Ok(commit), Some(since)) => commit.time() >= since,
+                _ => true,
+            })
+            .filter_map(move |commit| {
+                let commit = match commit {
+                    Ok(commit) => commit,
+                    Err(e) => return Some(Err(e)),
+                };
+
+                match self.matches_filter(&mut filter, &commit) {
+                    Ok(true) => Some(Ok(commit)),
+                    Ok(false) => None,
+                    Err(e) => Some(Err(e)),
+                }
+            })
+            .take(limit))
+    }
+
+    fn matches_filter(
+        &self,
+        filter: &mut CommitFilter,
+        commit: &Commit<'_>,
+    ) -> Result<bool, git2::Error> {
+        if !filter.matches(commit) {
+            return Ok(false);
+        }
+
+        if filter.paths.is_empty() {
+            return Ok(true);
+        }
+
+        let tree = commit.0.tree()?;
+        let parent_tree = match commit.0.parent_count() {
+            0 => None,
+            _ => Some(commit.0.parent(0)?.tree()?),
+        };
+
+        let mut opts = DiffOptions::new();
+
+        for path in &filter.paths {
+            opts.pathspec(path.as_path());
+        }
+
+        let diff =
+            self.repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+
+        if diff.deltas().len() == 0 {
+            return Ok(false);
+        }
+
+        // When the file was added in this commit it might have been renamed, in which case the
+        // older commits are filtered using the old path
+        if filter.follow() {
+            let path = filter.paths[0].as_path();
+            let added = diff.deltas().any(|delta| {
+                delta.status() == Delta::Added && delta.new_file().path() == Some(path)
+            });
+
+            if added {
+                let mut diff =
+                    self.repo
+                        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+                find_similar(&mut diff)?;
+
+                let renamed_from = diff
+                    .deltas()
+                    .find(|delta| {
+                        delta.status() == Delta::Renamed && delta.new_file().path() == Some(path)
+                    })
+                    .and_then(|delta| delta.old_file().path().map(Path::to_path_buf));
+
+                if let Some(old_path) = renamed_from {
+                    filter.paths[0] = old_path;
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    pub fn walk(
+        &self,
+        tips: &[git2::Oid],
+        sort: Sort,
+    ) -> Result<impl Iterator<Item = Result<Commit<'_>, git2::Error>>, git2::Error> {
+        let mut walker = self.repo.revwalk()?;
+        walker.set_sorting(sort)?;
+
+        for tip in tips {
+            walker.push(*tip)?;
+        }
+
         Ok(walker.map(|oid| oid.and_then(|oid| self.find_commit(oid))))
     }
 
+    // Commits reachable from `tip` but not from `base`, newest first
+    pub fn commits_between(
+        &self,
+        base: git2::Oid,
+        tip: git2::Oid,
+    ) -> Result<Vec<Commit<'_>>, git2::Error> {
+        let mut walker = self.repo.revwalk()?;
+        walker.set_sorting(Sort::TOPOLOGICAL)?;
+        walker.push(tip)?;
+        walker.hide(base)?;
+
+        walker
+            .map(|oid| oid.and_then(|oid| self.find_commit(oid)))
+            .collect()
+    }
+
+    pub fn references(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<Ref<'_>, git2::Error>> + '_, git2::Error> {
+        Ok(self.repo.references()?.map(|result| result.map(Into::into)))
+    }
+
+    // HEAD's reflog, newest first
+    pub fn reflog(&self) -> Result<Vec<reflog::Entry>, git2::Error> {
+        Ok(self
+            .repo
+            .reflog("HEAD")?
+            .iter()
+            .map(|entry| reflog::Entry {
+                old: entry.id_old(),
+                new: entry.id_new(),
+                message: entry.message().unwrap_or_default().to_string(),
+                time: super::parse_local_time(entry.committer().when()),
+            })
+            .collect())
+    }
+
+    // Replaces the message of the newest HEAD reflog entry, for operations whose reflog is
+    // written by libgit2
+    pub fn reword_reflog(&self, message: &str) -> Result<(), git2::Error> {
+        let mut reflog = self.repo.reflog("HEAD")?;
+        let Some((new, committer)) = reflog
+            .get(0)
+            .map(|entry| (entry.id_new(), entry.committer().to_owned()))
+        else {
+            return Ok(());
+        };
+
+        reflog.remove(0, false)?;
+        reflog.append(new, &committer, Some(message))?;
+        reflog.write()
+    }
+
     pub fn stashes(
-        &mut self,
+        &self,
     ) -> Result<impl Iterator<Item = Result<Commit<'_>, git2::Error>>, git2::Error> {
-        let mut stashes = vec![];
-
-        self.repo.stash_foreach(|_, _, oid| {
-            stashes.push(*oid);
-            true
-        })?;
+        let stashes = self
+            .repo
+            .reflog("refs/stash")?
+            .iter()
+            .map(|entry| entry.id_new())
+            .collect::<Vec<_>>();
 
         Ok(stashes.into_iter().map(|oid| self.find_commit(oid)))
     }
 
+    pub fn find_stash(&self, index: usize) -> Result<Option<git2::Oid>, git2::Error> {
+        let reflog = self.repo.reflog("refs/stash")?;
+        Ok(reflog.get(index).map(|entry| entry.id_new()))
+    }
+
     pub fn pop_stash(&mut self, index: usize) -> Result<(), git2::Error> {
         let mut cb = CheckoutBuilder::default();
         cb.safe();
@@ -250,26 +623,330 @@ impl Repo {
         )
     }
 
-    pub fn save_stash(&mut self, message: &str) -> Result<git2::Oid, StashError> {
-        let config = Config::open_default()?;
+    pub fn save_stash(&mut self, message: &str, sign: SignMode) -> Result<git2::Oid, StashError> {
+        let config = self.config()?;
         let signature = config.user.signature()?;
+        let oid = self
+            .repo
+            .stash_save(&signature, message, Some(StashFlags::INCLUDE_UNTRACKED))?;
 
-        Ok(self
+        if !sign.enabled(config.commit.gpg_sign) {
+            return Ok(oid);
+        }
+
+        // The stash API doesn't support signing, so re-create the stash commit and replace it.
+        // The index and untracked files are stored in parent commits, which are signed as well
+        let commit = self.repo.find_commit(oid)?;
+        let mut parents = vec![commit.parent(0)?];
+
+        for parent in commit.parents().skip(1) {
+            let grandparents = parent.parents().collect::<Vec<_>>();
+            let signed = self.resign_commit(&config, &parent, &grandparents)?;
+            parents.push(self.repo.find_commit(signed)?);
+        }
+
+        let signed = self.resign_commit(&config, &commit, &parents)?;
+
+        self.repo.reference("refs/stash", signed, true, message)?;
+
+        let mut reflog = self.repo.reflog("refs/stash")?;
+
+        if let Some(i) = reflog.iter().position(|entry| entry.id_new() == oid) {
+            reflog.remove(i, false)?;
+        }
+
+        if reflog.get(0).map(|entry| entry.id_new()) != Some(signed) {
+            reflog.append(signed, &signature, Some(message))?;
+        }
+
+        reflog.write()?;
+
+        Ok(signed)
+    }
+
+    fn resign_commit(
+        &self,
+        config: &Config,
+        commit: &git2::Commit<'_>,
+        parents: &[git2::Commit<'_>],
+    ) -> Result<git2::Oid, StashError> {
+        self.write_commit(
+            config,
+            &commit.author(),
+            &commit.committer(),
+            commit.message_raw().unwrap_or_default(),
+            &commit.tree()?.into(),
+            &parents.iter().collect::<Vec<_>>(),
+            SignMode::Always,
+        )
+        .map_err(StashError::Sign)
+    }
+
+    // Records the current state under `refs/src/backup/` before a destructive operation
+    pub fn backup(&self, reason: &str) -> Result<String, Box<dyn Error>> {
+        self.backup_with_refs(reason, &[])
+    }
+
+    // Also records other refs and keeps their commits
+    pub fn backup_with_refs(
+        &self,
+        reason: &str,
+        refs: &[(&str, git2::Oid)],
+    ) -> Result<String, Box<dyn Error>> {
+        let config = self.config()?;
+        let signature = config.user.signature()?;
+        let head = self.repo.head().and_then(|head| head.peel_to_commit()).ok();
+        let head_ref = self.repo.find_reference("HEAD")?;
+        let mut index = self.snapshot_index()?;
+        let index_tree = self.repo.find_tree(index.write_tree_to(&self.repo)?)?;
+        let worktree = self
+            .repo
+            .find_tree(self.snapshot_worktree(&index, &index_tree)?)?;
+        let head_parents = head.iter().collect::<Vec<_>>();
+
+        let mut message = format!("{reason}\n");
+
+        let mut trailers = head_ref
+            .symbolic_target()
+            .map(|name| format!("Head: {name}\n"))
+            .into_iter()
+            .chain(
+                refs.iter()
+                    .map(|(name, oid)| format!("Ref: {name} {oid}\n")),
+            )
+            .peekable();
+
+        if trailers.peek().is_some() {
+            message.push('\n');
+            message.extend(trailers);
+        }
+
+        let ref_commits = refs
+            .iter()
+            .map(|(_, oid)| self.repo.find_commit(*oid))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let index = self.repo.commit(
+            None,
+            &signature,
+            &signature,
+            "index",
+            &index_tree,
+            &head_parents,
+        )?;
+        let index = self.repo.find_commit(index)?;
+        let parents = [&index]
+            .into_iter()
+            .chain(head.iter())
+            .chain(ref_commits.iter())
+            .collect::<Vec<_>>();
+        let oid = self
+            .repo
+            .commit(None, &signature, &signature, &message, &worktree, &parents)?;
+
+        let time = Local::now().timestamp();
+        let mut name = time.to_string();
+        let mut suffix = 1;
+
+        while self
             .repo
-            .stash_save(&signature, message, Some(StashFlags::INCLUDE_UNTRACKED))?)
+            .find_reference(&format!("{BACKUP_REFS}{name}"))
+            .optional()?
+            .is_some()
+        {
+            name = format!("{time}-{suffix}");
+            suffix += 1;
+        }
+
+        self.repo
+            .reference(&format!("{BACKUP_REFS}{name}"), oid, false, reason)?;
+        self.prune_backups(config.backup.max_age_days)?;
+
+        Ok(name)
+    }
+
+    // The index without its conflicts, the conflicting files are part of the worktree snapshot
+    fn snapshot_index(&self) -> Result<git2::Index, git2::Error> {
+        let mut index = git2::Index::new()?;
+
+        for entry in self.repo.index()?.iter() {
+            if entry.flags & INDEX_STAGE_MASK == 0 {
+                index.add(&entry)?;
+            }
+        }
+
+        Ok(index)
+    }
+
+    // Builds a tree of the worktree on top of the index tree, including untracked files.
+    // Submodules keep the commit of the index and files that can't be read are left out
+    fn snapshot_worktree(
+        &self,
+        index: &git2::Index,
+        index_tree: &git2::Tree<'_>,
+    ) -> Result<git2::Oid, git2::Error> {
+        let Some(workdir) = self.repo.workdir() else {
+            return Ok(index_tree.id());
+        };
+
+        let mut opts = DiffOptions::new();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .ignore_submodules(true);
+
+        // Unlike comparing with the tree, the stat cache of the index avoids reading unchanged files
+        let diff = self
+            .repo
+            .diff_index_to_workdir(Some(index), Some(&mut opts))?;
+        let mut builder = TreeUpdateBuilder::new();
+
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path() else {
+                continue;
+            };
+            let file = workdir.join(path);
+
+            if delta.status() == Delta::Deleted {
+                builder.remove(path);
+                continue;
+            }
+
+            let mode = delta.new_file().mode();
+            let blob = match mode {
+                FileMode::Link => fs::read_link(&file)
+                    .ok()
+                    .and_then(|target| self.repo.blob(target.to_string_lossy().as_bytes()).ok()),
+                FileMode::Blob | FileMode::BlobExecutable => {
+                    let too_large = delta.status() == Delta::Untracked
+                        && fs::metadata(&file).is_ok_and(|meta| meta.len() > MAX_UNTRACKED_SIZE);
+
+                    if too_large {
+                        None
+                    } else {
+                        self.repo.blob_path(&file).ok()
+                    }
+                }
+                _ => None,
+            };
+
+            if let Some(blob) = blob {
+                builder.upsert(path, blob, mode);
+            }
+        }
+
+        builder.create_updated(&self.repo, index_tree)
+    }
+
+    // Newest first
+    pub fn backups(&self) -> Result<Vec<Backup<'_>>, git2::Error> {
+        let mut backups = vec![];
+
+        for reference in self.repo.references_glob(&format!("{BACKUP_REFS}*"))? {
+            let reference = reference?;
+            let Some(name) = reference
+                .name()
+                .and_then(|name| name.strip_prefix(BACKUP_REFS))
+            else {
+                continue;
+            };
+
+            backups.push(Backup::parse(name, reference.peel_to_commit()?.into()));
+        }
+
+        backups.sort_by(|a, b| b.time.cmp(&a.time));
+
+        Ok(backups)
+    }
+
+    fn prune_backups(&self, max_age_days: i64) -> Result<(), git2::Error> {
+        let cutoff = Local::now() - Duration::days(max_age_days);
+
+        for backup in self.backups()? {
+            if backup.time < cutoff {
+                self.repo
+                    .find_reference(&format!("{BACKUP_REFS}{}", backup.name))?
+                    .delete()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn find_backup(&self, name: &str) -> Result<Backup<'_>, Box<dyn Error>> {
+        let commit = self
+            .repo
+            .find_reference(&format!("{BACKUP_REFS}{name}"))
+            .optional()?
+            .ok_or_else(|| format!("Backup not found: {name}"))?
+            .peel_to_commit()?;
+
+        Ok(Backup::parse(name, commit.into()))
+    }
+
+    // Backs up the current state first so that restoring can be undone as well. The ref HEAD
+    // pointed at when backing up is moved back, whichever branch is checked out now
+    pub fn restore_backup(&self, name: &str) -> Result<String, Box<dyn Error>> {
+        let backup = self.find_backup(name)?;
+        let index_tree = backup.commit.0.parent(0)?.tree()?;
+        let current = self.backup(&format!("restore-backup {name}"))?;
+        let message = format!("restore-backup: {name}");
+
+        self.checkout_tree(&backup.commit.find_tree()?, true)?;
+
+        match (&backup.head_ref, backup.head()) {
+            (Some(head_ref), Some(head)) => {
+                self.repo.set_head(head_ref)?;
+                self.repo.reference(head_ref, head, true, &message)?;
+            }
+            // The branch had no commits yet
+            (Some(head_ref), None) => self.repo.set_head(head_ref)?,
+            (None, Some(head)) => self.repo.set_head_detached(head)?,
+            (None, None) => {}
+        }
+
+        let mut index = self.repo.index()?;
+        index.read_tree(&index_tree)?;
+        index.write()?;
+
+        Ok(current)
     }
 
     pub fn create_ref(&self, name: &str, target: git2::Oid) -> Result<Ref<'_>, git2::Error> {
         self.repo.reference(name, target, true, "").map(Into::into)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_commit(
+        &self,
+        config: &Config,
+        author: &git2::Signature<'_>,
+        committer: &git2::Signature<'_>,
+        message: &str,
+        tree: &Tree<'_>,
+        parents: &[&git2::Commit<'_>],
+        sign: SignMode,
+    ) -> Result<git2::Oid, Box<dyn Error>> {
+        let buf = self
+            .repo
+            .commit_create_buffer(author, committer, message, &tree.0, parents)?;
+
+        match signer::sign(config, sign, config.commit.gpg_sign, &buf)? {
+            Some(signature) => {
+                let content = std::str::from_utf8(&buf)?;
+                Ok(self.repo.commit_signed(content, &signature, None)?)
+            }
+            None => Ok(self.repo.odb()?.write(ObjectType::Commit, &buf)?),
+        }
+    }
+
     pub fn create_commit(
         &self,
         tree: &Tree<'_>,
         message: &str,
         parent: Option<&Commit<'_>>,
+        sign: SignMode,
     ) -> Result<git2::Oid, Box<dyn Error>> {
-        let config = Config::open_default()?;
+        let config = self.config()?;
         let author = config.user.signature()?;
         let parent_commit = match parent {
             Some(parent) => Some(Cow::Borrowed(&parent.0)),
@@ -284,25 +961,338 @@ impl Repo {
             .map(|c| vec![c.as_ref()])
             .unwrap_or_default();
 
-        if config.commit.gpg_sign {
-            match config.gpg.format {
-                Some(super::config::GpgFormat::Ssh) => {
-                    let signer = SshSigner::from_config(&config)?;
-                    let buf = self
-                        .repo
-                        .commit_create_buffer(&author, &author, message, &tree.0, &parents)?;
-                    let signed = signer.sign(&buf)?;
-                    let content = std::str::from_utf8(&buf)?;
-
-                    Ok(self.repo.commit_signed(content, &signed, None)?)
-                }
-                None => Err("gpg.format unsupported".into()),
+        self.write_commit(&config, &author, &author, message, tree, &parents, sign)
+    }
+
+    // Applies a commit on top of HEAD, when it conflicts the state is left for the user to resolve
+    pub fn cherry_pick(
+        &self,
+        commit: &Commit<'_>,
+        mainline: Option<u32>,
+        sign: SignMode,
+    ) -> Result<Applied, Box<dyn Error>> {
+        let mut opts = CherrypickOptions::new();
+
+        if let Some(mainline) = mainline {
+            opts.mainline(mainline);
+        }
+
+        self.repo.cherrypick(&commit.0, Some(&mut opts))?;
+        self.finish_cherry_pick(commit, sign)
+    }
+
+    pub fn finish_cherry_pick(
+        &self,
+        commit: &Commit<'_>,
+        sign: SignMode,
+    ) -> Result<Applied, Box<dyn Error>> {
+        let reflog = format!("cherry-pick: {}", commit.title());
+
+        self.commit_applied(Some(&commit.author()), commit.message()?, &reflog, sign)
+    }
+
+    // Applies the inverse of a commit on top of HEAD
+    pub fn revert(
+        &self,
+        commit: &Commit<'_>,
+        mainline: Option<u32>,
+        sign: SignMode,
+    ) -> Result<Applied, Box<dyn Error>> {
+        let mut opts = RevertOptions::new();
+
+        if let Some(mainline) = mainline {
+            opts.mainline(mainline);
+        }
+
+        self.repo.revert(&commit.0, Some(&mut opts))?;
+        self.finish_revert(commit, sign)
+    }
+
+    pub fn finish_revert(
+        &self,
+        commit: &Commit<'_>,
+        sign: SignMode,
+    ) -> Result<Applied, Box<dyn Error>> {
+        let title = commit.title();
+        let message = format!("revert: {title}\n\nThis reverts commit {}.\n", commit.id());
+
+        self.commit_applied(None, &message, &format!("revert: {title}"), sign)
+    }
+
+    // Commits the index left by a cherry-pick or revert, the author defaults to the committer
+    fn commit_applied(
+        &self,
+        author: Option<&git2::Signature<'_>>,
+        message: &str,
+        reflog: &str,
+        sign: SignMode,
+    ) -> Result<Applied, Box<dyn Error>> {
+        let mut index = self.repo.index()?;
+
+        if index.has_conflicts() {
+            return Ok(Applied::Conflict);
+        }
+
+        let head = self.repo.head()?.peel_to_commit()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+
+        if tree.id() == head.tree_id() {
+            self.repo.cleanup_state()?;
+            return Ok(Applied::Empty);
+        }
+
+        let config = self.config()?;
+        let committer = config.user.signature()?;
+        let oid = self.write_commit(
+            &config,
+            author.unwrap_or(&committer),
+            &committer,
+            message,
+            &tree.into(),
+            &[&head],
+            sign,
+        )?;
+
+        self.repo.head()?.set_target(oid, reflog)?;
+        self.repo.cleanup_state()?;
+
+        Ok(Applied::Commit(oid))
+    }
+
+    pub fn cherry_pick_head(&self) -> Result<Option<Commit<'_>>, git2::Error> {
+        self.find_state_head("CHERRY_PICK_HEAD")
+    }
+
+    pub fn revert_head(&self) -> Result<Option<Commit<'_>>, git2::Error> {
+        self.find_state_head("REVERT_HEAD")
+    }
+
+    fn find_state_head(&self, name: &str) -> Result<Option<Commit<'_>>, git2::Error> {
+        match self.repo.find_reference(name).optional()? {
+            Some(reference) => reference.peel_to_commit().map(|commit| Some(commit.into())),
+            None => Ok(None),
+        }
+    }
+
+    pub fn reset(&self, commit: &Commit<'_>, kind: ResetType) -> Result<(), git2::Error> {
+        self.repo.reset(commit.0.as_object(), kind, None)
+    }
+
+    // Commits from `base` up to HEAD, oldest first, history with merges can't be rewritten
+    pub fn commits_since<'r>(
+        &'r self,
+        base: &Commit<'_>,
+    ) -> Result<Vec<Commit<'r>>, Box<dyn Error>> {
+        let mut commits = vec![];
+        let mut commit = Some(self.repo.head()?.peel_to_commit()?);
+
+        while let Some(current) = commit {
+            if current.parent_count() > 1 {
+                return Err("Unable to rewrite history containing merge commits".into());
+            }
+
+            let done = current.id() == base.id();
+            commit = current.parents().next().filter(|_| !done);
+            commits.push(Commit::from(current));
+
+            if done {
+                commits.reverse();
+                return Ok(commits);
+            }
+        }
+
+        Err(format!("{} is not an ancestor of HEAD", base.id()).into())
+    }
+
+    // Replays the steps on top of `onto`, commits that stay the same keep their id and signature
+    pub fn replay(
+        &self,
+        onto: Option<git2::Oid>,
+        steps: &[Step<'_>],
+        sign: SignMode,
+    ) -> Result<Option<git2::Oid>, Box<dyn Error>> {
+        let config = self.config()?;
+        let committer = config.user.signature()?;
+        let mut current = onto.map(|oid| self.repo.find_commit(oid)).transpose()?;
+
+        for step in steps {
+            let commit = &step.commit().0;
+            let unchanged = commit.parent_ids().eq(current.iter().map(|c| c.id()));
+
+            if unchanged && matches!(step, Step::Pick(_)) {
+                current = Some(self.repo.find_commit(commit.id())?);
+                continue;
             }
+
+            let tree = self.apply_onto(commit, current.as_ref(), unchanged)?;
+            let oid = match (step, &current) {
+                (Step::Fixup(_), Some(previous)) => {
+                    let parents = previous.parents().collect::<Vec<_>>();
+                    let parents = parents.iter().collect::<Vec<_>>();
+
+                    self.write_commit(
+                        &config,
+                        &previous.author(),
+                        &committer,
+                        previous.message().unwrap_or_default(),
+                        &tree,
+                        &parents,
+                        sign,
+                    )?
+                }
+                (step, _) => {
+                    // Git ends messages with a newline, the edited message doesn't have one
+                    let message = match step {
+                        Step::Reword(_, message) if !message.ends_with('\n') => {
+                            Cow::Owned(format!("{message}\n"))
+                        }
+                        Step::Reword(_, message) => Cow::Borrowed(message.as_str()),
+                        _ => Cow::Borrowed(commit.message().unwrap_or_default()),
+                    };
+
+                    self.write_commit(
+                        &config,
+                        &commit.author(),
+                        &committer,
+                        &message,
+                        &tree,
+                        &current.iter().collect::<Vec<_>>(),
+                        sign,
+                    )?
+                }
+            };
+
+            current = Some(self.repo.find_commit(oid)?);
+        }
+
+        Ok(current.map(|commit| commit.id()))
+    }
+
+    fn apply_onto(
+        &self,
+        commit: &git2::Commit<'_>,
+        onto: Option<&git2::Commit<'_>>,
+        unchanged: bool,
+    ) -> Result<Tree<'_>, Box<dyn Error>> {
+        if unchanged {
+            return Ok(self.repo.find_tree(commit.tree_id())?.into());
+        }
+
+        let onto = onto.ok_or("Unable to move a commit before the root commit")?;
+        let mut index = self.repo.cherrypick_commit(commit, onto, 0, None)?;
+
+        if index.has_conflicts() {
+            return Err(format!(
+                "Rewriting {} results in conflicts, nothing was changed",
+                commit.id()
+            )
+            .into());
+        }
+
+        Ok(self
+            .repo
+            .find_tree(index.write_tree_to(&self.repo)?)?
+            .into())
+    }
+
+    // Writes new content for files of a tree, entries that aren't listed are kept
+    pub fn update_files(
+        &self,
+        tree: &Tree<'_>,
+        files: &[(&Path, FileMode, Vec<u8>)],
+    ) -> Result<Tree<'_>, git2::Error> {
+        let mut builder = TreeUpdateBuilder::new();
+
+        for (path, mode, content) in files {
+            builder.upsert(path, self.repo.blob(content)?, *mode);
+        }
+
+        self.find_tree(builder.create_updated(&self.repo, &tree.0)?)
+    }
+
+    // Sets entries of a tree to existing blobs, `None` removes the entry
+    pub fn update_entries(
+        &self,
+        tree: &Tree<'_>,
+        entries: &[(&Path, Option<(git2::Oid, FileMode)>)],
+    ) -> Result<Tree<'_>, git2::Error> {
+        let mut builder = TreeUpdateBuilder::new();
+
+        for (path, entry) in entries {
+            match entry {
+                Some((oid, mode)) => builder.upsert(path, *oid, *mode),
+                None => builder.remove(path),
+            };
+        }
+
+        self.find_tree(builder.create_updated(&self.repo, &tree.0)?)
+    }
+
+    // Points HEAD at a rewritten commit, the worktree is kept and the index matches the commit
+    pub fn move_head(&self, oid: git2::Oid, message: &str) -> Result<(), git2::Error> {
+        self.repo.head()?.set_target(oid, message)?;
+
+        let mut index = self.repo.index()?;
+        index.read_tree(&self.repo.find_commit(oid)?.tree()?)?;
+        index.write()
+    }
+
+    // Whether the index or a tracked file differs from HEAD, untracked files don't count
+    pub fn has_changes(&self) -> Result<bool, git2::Error> {
+        let statuses = self.repo.statuses(Some(
+            StatusOptions::new()
+                .include_untracked(false)
+                .include_ignored(false)
+                .exclude_submodules(true),
+        ))?;
+
+        Ok(!statuses.is_empty())
+    }
+
+    // Throws away the changes of a conflicting cherry-pick or revert, these only start from a
+    // clean worktree so nothing else is lost
+    pub fn abort(&self) -> Result<(), git2::Error> {
+        let head = self.repo.head()?.peel_to_commit()?;
+
+        self.repo.reset(head.as_object(), ResetType::Hard, None)?;
+        self.repo.cleanup_state()
+    }
+
+    pub fn create_tag(
+        &self,
+        name: &str,
+        target: &Commit<'_>,
+        message: &str,
+        sign: SignMode,
+    ) -> Result<git2::Oid, Box<dyn Error>> {
+        let config = self.config()?;
+        let tagger = config.user.signature()?;
+        let message = if message.ends_with('\n') {
+            message.to_string()
         } else {
-            Ok(self
-                .repo
-                .commit(None, &author, &author, message, &tree.0, &parents)?)
+            format!("{message}\n")
+        };
+        let mut content = format!(
+            "object {}\ntype commit\ntag {name}\ntagger {}\n\n{message}",
+            target.id(),
+            format_signature(&tagger),
+        );
+
+        // Unlike commits, the signature of a tag is appended to the message
+        if let Some(signature) =
+            signer::sign(&config, sign, config.tag.gpg_sign, content.as_bytes())?
+        {
+            content.push_str(&signature);
         }
+
+        let oid = self
+            .repo
+            .odb()?
+            .write(ObjectType::Tag, content.as_bytes())?;
+        self.repo
+            .reference(&format!("refs/tags/{name}"), oid, false, "tag: created")?;
+
+        Ok(oid)
     }
 
     pub fn diff(&self, mut opts: DiffOpts) -> Result<git2::Diff, git2::Error> {
@@ -314,17 +1304,65 @@ impl Repo {
                 self.repo
                     .diff_tree_to_index(Some(&tree.0), None, Some(&mut opts.diff_opts))?
             }
+            DiffType::Trees(old, new) => self.repo.diff_tree_to_tree(
+                Some(&old.0),
+                Some(&new.0),
+                Some(&mut opts.diff_opts),
+            )?,
             DiffType::Unstaged => self
                 .repo
                 .diff_index_to_workdir(None, Some(&mut opts.diff_opts))?,
         };
 
-        let mut find_opts = DiffFindOptions::new();
-        diff.find_similar(Some(find_opts.renames(true).copies(true)))?;
+        find_similar(&mut diff)?;
+        Ok(diff)
+    }
+
+    // Changes of a commit compared to its first parent
+    pub fn diff_commit(&self, commit: &Commit<'_>) -> Result<git2::Diff<'_>, git2::Error> {
+        let tree = commit.0.tree()?;
+        let parent_tree = match commit.0.parent_count() {
+            0 => None,
+            _ => Some(commit.0.parent(0)?.tree()?),
+        };
+
+        let mut opts = DiffOpts::default();
+        let mut diff = self.repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&tree),
+            Some(&mut opts.diff_opts),
+        )?;
 
+        find_similar(&mut diff)?;
         Ok(diff)
     }
 
+    pub fn blame(
+        &self,
+        path: &Path,
+        newest: Option<git2::Oid>,
+        lines: Option<(usize, usize)>,
+    ) -> Result<git2::Blame<'_>, git2::Error> {
+        let mut opts = BlameOptions::new();
+
+        if let Some(oid) = newest {
+            opts.newest_commit(oid);
+        }
+
+        if let Some((start, end)) = lines {
+            opts.min_line(start).max_line(end);
+        }
+
+        self.repo.blame_file(path, Some(&mut opts))
+    }
+
+    pub fn read_file(&self, commit: &Commit<'_>, path: &Path) -> Result<Vec<u8>, git2::Error> {
+        let entry = commit.0.tree()?.get_path(path)?;
+        let blob = self.repo.find_blob(entry.id())?;
+
+        Ok(blob.content().to_vec())
+    }
+
     pub fn index(&self) -> Result<Index, git2::Error> {
         self.repo.index().map(Into::into)
     }
@@ -343,3 +1381,98 @@ impl Repo {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::rewrite;
+
+    fn init(dir: &TempDir) -> Repo {
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        config.set_bool("commit.gpgsign", false).unwrap();
+
+        Repo::from(repo)
+    }
+
+    // Commits the files on top of HEAD without touching the worktree
+    fn commit(repo: &Repo, files: &[(&str, &str)], message: &str) -> git2::Oid {
+        let head = repo.repo.head().and_then(|head| head.peel_to_commit()).ok();
+        let base = match &head {
+            Some(head) => head.tree().unwrap(),
+            None => {
+                let empty = repo.repo.treebuilder(None).unwrap().write().unwrap();
+                repo.repo.find_tree(empty).unwrap()
+            }
+        };
+        let files = files
+            .iter()
+            .map(|(path, content)| (Path::new(path), FileMode::Blob, content.as_bytes().to_vec()))
+            .collect::<Vec<_>>();
+        let tree = repo.update_files(&Tree::from(base), &files).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+
+        repo.repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree.0,
+                &head.iter().collect::<Vec<_>>(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_replay_fixup() {
+        let dir = TempDir::new().unwrap();
+        let repo = init(&dir);
+        let base = commit(&repo, &[("a", "a\n"), ("b", "b\n")], "base");
+        let first = commit(&repo, &[("a", "a1\n")], "first");
+        commit(&repo, &[("b", "b1\n")], "second");
+        commit(&repo, &[("a", "a2\n")], "fixup! first");
+
+        let commits = repo
+            .commits_since(&repo.find_commit(first).unwrap())
+            .unwrap();
+        let head = repo
+            .replay(Some(base), &rewrite::autosquash(commits), SignMode::Never)
+            .unwrap()
+            .unwrap();
+        let head = repo.find_commit(head).unwrap();
+        let parent = head.parent().unwrap().unwrap();
+
+        assert_eq!(head.title(), "second");
+        assert_eq!(parent.title(), "first");
+        assert_eq!(parent.parent().unwrap().unwrap().id(), base);
+        assert_eq!(repo.read_file(&head, Path::new("a")).unwrap(), b"a2\n");
+        assert_eq!(repo.read_file(&head, Path::new("b")).unwrap(), b"b1\n");
+    }
+
+    #[test]
+    fn test_replay_conflicting_fixup() {
+        let dir = TempDir::new().unwrap();
+        let repo = init(&dir);
+        let base = commit(&repo, &[("a", "a\n")], "base");
+        let first = commit(&repo, &[("a", "a1\n")], "first");
+        commit(&repo, &[("a", "a2\n")], "second");
+        let head = commit(&repo, &[("a", "a3\n")], "fixup! first");
+
+        // The fixup changes a line that only exists after `second`
+        let commits = repo
+            .commits_since(&repo.find_commit(first).unwrap())
+            .unwrap();
+        let err = repo
+            .replay(Some(base), &rewrite::autosquash(commits), SignMode::Never)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("conflicts"));
+        assert_eq!(repo.head().unwrap().target().unwrap(), head);
+    }
+}
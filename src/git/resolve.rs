@@ -1,7 +1,8 @@
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_till},
-    character::complete::i32,
+    character::complete::{digit1, i32},
+    combinator::map_res,
     IResult, Parser,
 };
 
@@ -11,9 +12,16 @@ use super::{Optional, Repo};
 pub enum Pattern<'a> {
     Head,
     Branch(&'a str),
+    Stash(usize),
     Parent((usize, Box<Pattern<'a>>)),
 }
 
+fn stash(pattern: &str) -> IResult<&str, Pattern<'_>> {
+    let index = map_res(digit1, str::parse::<usize>);
+    let (input, (_, n, _)) = (tag("stash@{"), index, tag("}")).parse(pattern)?;
+    Ok((input, Pattern::Stash(n)))
+}
+
 fn prefix(pattern: &str) -> IResult<&str, Pattern<'_>> {
     let (input, name) = alt((
         tag("HEAD"),
@@ -35,7 +43,7 @@ fn parent(pattern: &str) -> IResult<&str, Pattern<'_>> {
 
 impl<'a> Pattern<'a> {
     pub fn parse(pattern: &'a str) -> IResult<&'a str, Self> {
-        let (input, name) = alt((parent, prefix)).parse(pattern)?;
+        let (input, name) = alt((stash, parent, prefix)).parse(pattern)?;
         Ok((input, name))
     }
 
@@ -43,6 +51,7 @@ impl<'a> Pattern<'a> {
         match self {
             Pattern::Head => Ok(Some(repo.head()?.target()?)),
             Pattern::Branch(name) => repo.find_branch(name).and_then(|b| b.target()).optional(),
+            Pattern::Stash(n) => repo.find_stash(*n),
             Pattern::Parent((n, pat)) => match pat.resolve(repo)? {
                 Some(oid) => Ok(repo.find_commit(oid)?.parent_n(*n)?.map(|c| c.id())),
                 None => Ok(None),
@@ -73,4 +82,18 @@ mod tests {
             Pattern::Parent((2, Box::new(Pattern::Branch("main"))))
         );
     }
+
+    #[test]
+    fn test_stash() {
+        let pattern = "stash@{3}";
+        let (input, pattern) = Pattern::parse(pattern).unwrap();
+        assert_eq!(input, "");
+        assert_eq!(pattern, Pattern::Stash(3));
+
+        // A negative index isn't a stash, it's left to git to resolve
+        assert!(!matches!(
+            Pattern::parse("stash@{-1}"),
+            Ok((_, Pattern::Stash(_)))
+        ));
+    }
 }
@@ -0,0 +1,79 @@
+use super::Commit;
+
+// What to do with each commit when replaying history
+pub enum Step<'a> {
+    Pick(Commit<'a>),
+    Reword(Commit<'a>, String),
+    // Folds the changes into the previous commit, keeping its message and author
+    Fixup(Commit<'a>),
+}
+
+impl<'a> Step<'a> {
+    pub fn commit(&self) -> &Commit<'a> {
+        match self {
+            Step::Pick(commit) | Step::Reword(commit, _) | Step::Fixup(commit) => commit,
+        }
+    }
+}
+
+// Moves every "fixup! <title>" right after the earlier commit with that title, returning the
+// indices in their new order and whether the commit is folded into the previous one
+pub fn autosquash_order(titles: &[&str]) -> Vec<(usize, bool)> {
+    let mut order = vec![];
+    let mut moved = vec![false; titles.len()];
+
+    for (i, title) in titles.iter().enumerate() {
+        if moved[i] {
+            continue;
+        }
+
+        order.push((i, false));
+
+        for (j, other) in titles.iter().enumerate().skip(i + 1) {
+            if !moved[j] && other.strip_prefix("fixup! ") == Some(title) {
+                order.push((j, true));
+                moved[j] = true;
+            }
+        }
+    }
+
+    order
+}
+
+// Commits are expected oldest first
+pub fn autosquash(commits: Vec<Commit<'_>>) -> Vec<Step<'_>> {
+    let titles = commits.iter().map(Commit::title).collect::<Vec<_>>();
+    let order = autosquash_order(&titles);
+    let mut commits = commits.into_iter().map(Some).collect::<Vec<_>>();
+
+    order
+        .into_iter()
+        .filter_map(|(i, fixup)| {
+            let commit = commits[i].take()?;
+            Some(if fixup {
+                Step::Fixup(commit)
+            } else {
+                Step::Pick(commit)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autosquash_order() {
+        assert_eq!(
+            autosquash_order(&["a", "b", "fixup! a", "c", "fixup! a"]),
+            vec![(0, false), (2, true), (4, true), (1, false), (3, false)]
+        );
+
+        // Without an earlier target the fixup stays where it is
+        assert_eq!(
+            autosquash_order(&["fixup! b", "b"]),
+            vec![(0, false), (1, false)]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use tempfile::NamedTempFile;
+
+use crate::git::{config::GpgFormat, Config};
+
+use super::{Signer, Verification, Verifier};
+
+const STATUS_PREFIX: &str = "[GNUPG:] ";
+
+fn default_program(format: &GpgFormat) -> &'static str {
+    match format {
+        GpgFormat::X509 => "gpgsm",
+        _ => "gpg",
+    }
+}
+
+fn program<'c>(config: &'c Config, format: &GpgFormat) -> &'c str {
+    let name = match format {
+        GpgFormat::X509 => "x509",
+        _ => "openpgp",
+    };
+
+    config
+        .gpg
+        .config
+        .get(name)
+        .and_then(|config| config.program.as_deref())
+        .or(match format {
+            GpgFormat::OpenPgp => config.gpg.program.as_deref(),
+            _ => None,
+        })
+        .unwrap_or(default_program(format))
+}
+
+// See: https://github.com/gpg/gnupg/blob/master/doc/DETAILS
+fn parse_status(status: &str) -> Verification {
+    let mut verification = Verification::Bad;
+    let mut trusted = false;
+
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix(STATUS_PREFIX) else {
+            continue;
+        };
+
+        let (keyword, args) = line.split_once(' ').unwrap_or((line, ""));
+
+        match keyword {
+            "GOODSIG" => {
+                let uid = args.split_once(' ').map(|(_, uid)| uid).unwrap_or(args);
+                verification = Verification::Good(uid.to_string());
+            }
+            "BADSIG" => return Verification::Bad,
+            "ERRSIG" | "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" => {
+                verification = Verification::Untrusted
+            }
+            "TRUST_FULLY" | "TRUST_ULTIMATE" => trusted = true,
+            _ => {}
+        }
+    }
+
+    match verification {
+        Verification::Good(_) if !trusted => Verification::Untrusted,
+        verification => verification,
+    }
+}
+
+pub struct GpgSigner<'c> {
+    program: &'c str,
+    signing_key: String,
+}
+
+impl<'c> GpgSigner<'c> {
+    pub fn new(program: &'c str, signing_key: String) -> Self {
+        Self {
+            program,
+            signing_key,
+        }
+    }
+
+    pub fn from_config(config: &'c Config, format: &GpgFormat) -> Self {
+        let signing_key = match config.user.signing_key.as_ref() {
+            Some(signing_key) => signing_key.clone(),
+            None => format!(
+                "{} <{}>",
+                config.user.name.as_deref().unwrap_or_default(),
+                config.user.email
+            ),
+        };
+
+        Self::new(program(config, format), signing_key)
+    }
+}
+
+impl Signer for GpgSigner<'_> {
+    fn sign(&self, content: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+        // See: https://github.com/git/git/blob/34b6ce9b30747131b6e781ff718a45328aa887d0/gpg-interface.c#L981
+        let mut child = Command::new(self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(["--status-fd=2", "-bsau"])
+            .arg(&self.signing_key)
+            .spawn()?;
+
+        let stdin = child.stdin.as_mut().unwrap();
+        stdin.write_all(content)?;
+        stdin.flush()?;
+
+        let output = child.wait_with_output()?;
+        let status = String::from_utf8_lossy(&output.stderr);
+
+        if !output.status.success() || !status.contains("[GNUPG:] SIG_CREATED ") {
+            return Err(format!("failed to sign: {status}").into());
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+pub struct GpgVerifier<'c> {
+    program: &'c str,
+}
+
+impl<'c> GpgVerifier<'c> {
+    pub fn from_config(config: &'c Config, format: &GpgFormat) -> Self {
+        Self {
+            program: program(config, format),
+        }
+    }
+}
+
+impl Verifier for GpgVerifier<'_> {
+    fn verify(
+        &self,
+        signature: &[u8],
+        payload: &[u8],
+    ) -> Result<Verification, Box<dyn std::error::Error>> {
+        let mut tmp = NamedTempFile::new()?;
+        tmp.write_all(signature)?;
+        tmp.flush()?;
+
+        let mut child = Command::new(self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .args(["--status-fd=1", "--verify"])
+            .arg(tmp.path())
+            .arg("-")
+            .spawn()?;
+
+        let stdin = child.stdin.as_mut().unwrap();
+        stdin.write_all(payload)?;
+        stdin.flush()?;
+
+        let output = child.wait_with_output()?;
+
+        Ok(parse_status(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_good_trusted() {
+        let status = "[GNUPG:] NEWSIG\n\
+            [GNUPG:] GOODSIG 0123456789ABCDEF John Doe <john@example.com>\n\
+            [GNUPG:] VALIDSIG 0123456789ABCDEF 2025-01-01\n\
+            [GNUPG:] TRUST_ULTIMATE 0 pgp\n";
+
+        assert_eq!(
+            parse_status(status),
+            Verification::Good("John Doe <john@example.com>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_good_untrusted() {
+        let status = "[GNUPG:] GOODSIG 0123456789ABCDEF John Doe <john@example.com>\n\
+            [GNUPG:] TRUST_UNDEFINED 0 pgp\n";
+
+        assert_eq!(parse_status(status), Verification::Untrusted);
+    }
+
+    #[test]
+    fn test_bad() {
+        let status = "[GNUPG:] BADSIG 0123456789ABCDEF John Doe <john@example.com>\n";
+        assert_eq!(parse_status(status), Verification::Bad);
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let status = "[GNUPG:] ERRSIG 0123456789ABCDEF 1 8 00 1700000000 9 -\n\
+            [GNUPG:] NO_PUBKEY 0123456789ABCDEF\n";
+
+        assert_eq!(parse_status(status), Verification::Untrusted);
+    }
+}
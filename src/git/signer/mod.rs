@@ -1,9 +1,131 @@
 use std::error::Error;
 
-use git2::Buf;
+use crate::term::node::prelude::*;
 
+use super::{config::GpgFormat, Config};
+
+pub mod gpg;
 pub mod ssh;
 
+const SSH_SIGNATURE_PREFIX: &[u8] = b"-----BEGIN SSH SIGNATURE-----";
+const PGP_SIGNATURE_PREFIXES: [&[u8]; 2] = [
+    b"-----BEGIN PGP SIGNATURE-----",
+    b"-----BEGIN PGP MESSAGE-----",
+];
+const X509_SIGNATURE_PREFIX: &[u8] = b"-----BEGIN SIGNED MESSAGE-----";
+
 pub trait Signer {
-    fn sign(&self, content: &Buf) -> Result<String, Box<dyn Error>>;
+    fn sign(&self, content: &[u8]) -> Result<String, Box<dyn Error>>;
+}
+
+pub trait Verifier {
+    fn verify(&self, signature: &[u8], payload: &[u8]) -> Result<Verification, Box<dyn Error>>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum SignMode {
+    #[default]
+    Config,
+    Always,
+    Never,
+}
+
+impl SignMode {
+    pub fn from_flags(sign: bool, no_sign: bool) -> Self {
+        match (sign, no_sign) {
+            (true, _) => SignMode::Always,
+            (_, true) => SignMode::Never,
+            _ => SignMode::Config,
+        }
+    }
+
+    pub fn enabled(&self, default: bool) -> bool {
+        match self {
+            SignMode::Config => default,
+            SignMode::Always => true,
+            SignMode::Never => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verification {
+    Good(String),
+    Bad,
+    Untrusted,
+    // Signed in a format that no verifier supports
+    Unknown,
+    Unsigned,
+}
+
+impl Verification {
+    pub fn icon(&self) -> Node {
+        match self {
+            Verification::Good(_) => icon!(Lock).with_status(Status::Success),
+            Verification::Untrusted => icon!(Lock).with_status(Status::Warning),
+            Verification::Bad => icon!(Cross).with_status(Status::Error),
+            Verification::Unknown => dimmed!(icon!(Lock)),
+            Verification::Unsigned => spacer!(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Verification::Good(principal) => format!("Good signature from {principal}"),
+            Verification::Bad => "Bad signature".to_string(),
+            Verification::Untrusted => "Signature from an unknown or untrusted key".to_string(),
+            Verification::Unknown => "Signature in an unsupported format".to_string(),
+            Verification::Unsigned => "Not signed".to_string(),
+        }
+    }
+}
+
+pub fn verify(
+    config: &Config,
+    signature: &[u8],
+    payload: &[u8],
+) -> Result<Verification, Box<dyn Error>> {
+    if signature.is_empty() {
+        return Ok(Verification::Unsigned);
+    }
+
+    if signature.starts_with(SSH_SIGNATURE_PREFIX) {
+        return ssh::SshVerifier::from_config(config).verify(signature, payload);
+    }
+
+    if PGP_SIGNATURE_PREFIXES
+        .iter()
+        .any(|prefix| signature.starts_with(prefix))
+    {
+        return gpg::GpgVerifier::from_config(config, &GpgFormat::OpenPgp)
+            .verify(signature, payload);
+    }
+
+    if signature.starts_with(X509_SIGNATURE_PREFIX) {
+        return gpg::GpgVerifier::from_config(config, &GpgFormat::X509).verify(signature, payload);
+    }
+
+    Ok(Verification::Unknown)
+}
+
+pub fn from_config(config: &Config) -> Result<Box<dyn Signer + '_>, Box<dyn Error>> {
+    let signer: Box<dyn Signer + '_> = match &config.gpg.format {
+        GpgFormat::Ssh => Box::new(ssh::SshSigner::from_config(config)?),
+        format => Box::new(gpg::GpgSigner::from_config(config, format)),
+    };
+
+    Ok(signer)
+}
+
+pub fn sign(
+    config: &Config,
+    mode: SignMode,
+    default: bool,
+    content: &[u8],
+) -> Result<Option<String>, Box<dyn Error>> {
+    if !mode.enabled(default) {
+        return Ok(None);
+    }
+
+    Ok(Some(from_config(config)?.sign(content)?))
 }
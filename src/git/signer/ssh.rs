@@ -1,27 +1,75 @@
 use std::{
     io::Write,
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
 };
 
+use resolve_path::PathResolveExt;
 use tempfile::NamedTempFile;
 
-use crate::git::{config::GpgFormat, Config};
+use crate::git::Config;
 
-use super::Signer;
+use super::{Signer, Verification, Verifier};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("missing signing key")]
     MissingSigningKey,
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("gpg.ssh.defaultKeyCommand failed: {0}")]
+    DefaultKeyCommand(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SigningKey {
+    Literal(String),
+    Path(PathBuf),
+}
+
+impl SigningKey {
+    pub fn parse(value: &str) -> Self {
+        if let Some(key) = value.strip_prefix("key::") {
+            return SigningKey::Literal(key.to_string());
+        }
+
+        // Deprecated, but still supported by git
+        if value.starts_with("ssh-") {
+            return SigningKey::Literal(value.to_string());
+        }
+
+        SigningKey::Path(Path::new(value).resolve().into_owned())
+    }
+
+    fn from_command(command: &str) -> Result<Self, Error> {
+        let output = Command::new("sh").arg("-c").arg(command).output()?;
+
+        if !output.status.success() {
+            return Err(Error::DefaultKeyCommand(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let key = stdout.lines().next().unwrap_or_default().trim();
+
+        if !key.starts_with("key::") && !key.starts_with("ssh-") {
+            return Err(Error::DefaultKeyCommand(format!(
+                "unexpected output: {key}"
+            )));
+        }
+
+        Ok(Self::parse(key))
+    }
 }
 
 pub struct SshSigner<'c> {
-    signing_key: &'c str,
+    signing_key: SigningKey,
     program: Option<&'c str>,
 }
 
 impl<'c> SshSigner<'c> {
-    pub fn new(signing_key: &'c str, program: Option<&'c str>) -> Self {
+    pub fn new(signing_key: SigningKey, program: Option<&'c str>) -> Self {
         Self {
             signing_key,
             program,
@@ -29,47 +77,46 @@ impl<'c> SshSigner<'c> {
     }
 
     pub fn from_config(config: &'c Config) -> Result<Self, Error> {
-        let signing_key = config
-            .user
-            .signing_key
-            .as_ref()
-            .ok_or(Error::MissingSigningKey)?;
+        let ssh = config.gpg.config.get("ssh");
+        let signing_key = match config.user.signing_key.as_deref() {
+            Some(signing_key) => SigningKey::parse(signing_key),
+            None => match ssh.and_then(|config| config.default_key_command.as_deref()) {
+                Some(command) => SigningKey::from_command(command)?,
+                None => return Err(Error::MissingSigningKey),
+            },
+        };
 
         Ok(Self::new(
             signing_key,
-            config.gpg.format.as_ref().and_then(|format| match format {
-                GpgFormat::Ssh => config
-                    .gpg
-                    .config
-                    .get("ssh")
-                    .and_then(|config| config.program.as_deref()),
-            }),
+            ssh.and_then(|config| config.program.as_deref()),
         ))
     }
 }
 
 impl Signer for SshSigner<'_> {
-    fn sign(&self, content: &git2::Buf) -> Result<String, Box<dyn std::error::Error>> {
-        // Aparently, we have to write this to a file
-        let mut tmp = NamedTempFile::new()?;
-        tmp.write_all(self.signing_key.as_bytes())?;
-        tmp.flush()?;
+    fn sign(&self, content: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+        let program = self.program.unwrap_or("ssh-keygen");
+        let mut command = Command::new(program);
+        command.args(["-Y", "sign", "-n", "git"]);
 
-        let program = self.program.unwrap_or("ssh");
+        // Literal keys have to be written to a file, the private key is then looked up in the ssh-agent
+        let _tmp = match &self.signing_key {
+            SigningKey::Literal(key) => {
+                let mut tmp = NamedTempFile::new()?;
+                tmp.write_all(key.as_bytes())?;
+                tmp.flush()?;
 
-        // See: https://github.com/git/git/blob/34b6ce9b30747131b6e781ff718a45328aa887d0/gpg-interface.c#L1072
-        let mut child = Command::new(program)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .args(["-Y", "sign", "-n", "git", "-f"])
-            .arg(tmp.path())
-            .spawn()?;
+                command.arg("-U").arg("-f").arg(tmp.path());
+                Some(tmp)
+            }
+            SigningKey::Path(path) => {
+                command.arg("-f").arg(path);
+                None
+            }
+        };
 
-        let stdin = child.stdin.as_mut().unwrap();
-        stdin.write_all(content)?;
-        stdin.flush()?;
-
-        let output = child.wait_with_output()?;
+        // See: https://github.com/git/git/blob/34b6ce9b30747131b6e781ff718a45328aa887d0/gpg-interface.c#L1072
+        let output = run_with_stdin(&mut command, content)?;
 
         if !output.status.success() {
             return Err(format!("failed to sign: {}", String::from_utf8(output.stderr)?).into());
@@ -78,3 +125,188 @@ impl Signer for SshSigner<'_> {
         Ok(String::from_utf8(output.stdout)?)
     }
 }
+
+fn run_with_stdin(command: &mut Command, input: &[u8]) -> Result<Output, std::io::Error> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdin = child.stdin.as_mut().unwrap();
+    stdin.write_all(input)?;
+    stdin.flush()?;
+
+    child.wait_with_output()
+}
+
+pub struct SshVerifier<'c> {
+    program: &'c str,
+    allowed_signers_file: Option<&'c str>,
+}
+
+impl<'c> SshVerifier<'c> {
+    pub fn new(program: Option<&'c str>, allowed_signers_file: Option<&'c str>) -> Self {
+        Self {
+            program: program.unwrap_or("ssh-keygen"),
+            allowed_signers_file,
+        }
+    }
+
+    pub fn from_config(config: &'c Config) -> Self {
+        let ssh = config.gpg.config.get("ssh");
+
+        Self::new(
+            ssh.and_then(|config| config.program.as_deref()),
+            ssh.and_then(|config| config.allowed_signers_file.as_deref()),
+        )
+    }
+
+    fn find_principal(
+        &self,
+        allowed_signers_file: &Path,
+        signature_file: &Path,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let output = Command::new(self.program)
+            .args(["-Y", "find-principals", "-f"])
+            .arg(allowed_signers_file)
+            .arg("-s")
+            .arg(signature_file)
+            .stderr(Stdio::null())
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .next()
+            .map(ToString::to_string))
+    }
+}
+
+impl Verifier for SshVerifier<'_> {
+    fn verify(
+        &self,
+        signature: &[u8],
+        payload: &[u8],
+    ) -> Result<Verification, Box<dyn std::error::Error>> {
+        let mut tmp = NamedTempFile::new()?;
+        tmp.write_all(signature)?;
+        tmp.flush()?;
+
+        // See: https://github.com/git/git/blob/34b6ce9b30747131b6e781ff718a45328aa887d0/gpg-interface.c#L514
+        if let Some(allowed_signers_file) = self.allowed_signers_file {
+            let allowed_signers_file = Path::new(allowed_signers_file).resolve();
+
+            if let Some(principal) = self.find_principal(&allowed_signers_file, tmp.path())? {
+                let output = run_with_stdin(
+                    Command::new(self.program)
+                        .args(["-Y", "verify", "-n", "git", "-f"])
+                        .arg(&*allowed_signers_file)
+                        .arg("-I")
+                        .arg(&principal)
+                        .arg("-s")
+                        .arg(tmp.path()),
+                    payload,
+                )?;
+
+                return Ok(if output.status.success() {
+                    Verification::Good(principal)
+                } else {
+                    Verification::Bad
+                });
+            }
+        }
+
+        // The key is unknown, but we can still check if the signature itself is valid
+        let output = run_with_stdin(
+            Command::new(self.program)
+                .args(["-Y", "check-novalidate", "-n", "git", "-s"])
+                .arg(tmp.path()),
+            payload,
+        )?;
+
+        Ok(if output.status.success() {
+            Verification::Untrusted
+        } else {
+            Verification::Bad
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn generate_key(dir: &TempDir) -> Option<PathBuf> {
+        let path = dir.path().join("id_ed25519");
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+            .arg(&path)
+            .status()
+            .ok()?;
+
+        status.success().then_some(path)
+    }
+
+    #[test]
+    fn test_parse_signing_key() {
+        assert_eq!(
+            SigningKey::parse("key::ssh-ed25519 AAAA test"),
+            SigningKey::Literal("ssh-ed25519 AAAA test".to_string())
+        );
+        assert_eq!(
+            SigningKey::parse("ssh-ed25519 AAAA test"),
+            SigningKey::Literal("ssh-ed25519 AAAA test".to_string())
+        );
+        assert_eq!(
+            SigningKey::parse("/tmp/id_ed25519.pub"),
+            SigningKey::Path(PathBuf::from("/tmp/id_ed25519.pub"))
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        // Signing goes through ssh-keygen, without it there's nothing to test
+        if which::which("ssh-keygen").is_err() {
+            return;
+        }
+
+        let dir = TempDir::new().unwrap();
+        let key = generate_key(&dir).expect("ssh-keygen failed to generate a key");
+
+        let payload = b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\ntest\n";
+        let signature = SshSigner::new(SigningKey::Path(key.clone()), None)
+            .sign(payload)
+            .unwrap();
+
+        assert!(signature.starts_with("-----BEGIN SSH SIGNATURE-----"));
+
+        let verifier = SshVerifier::new(None, None);
+        assert_eq!(
+            verifier.verify(signature.as_bytes(), payload).unwrap(),
+            Verification::Untrusted
+        );
+        assert_eq!(
+            verifier.verify(signature.as_bytes(), b"tampered").unwrap(),
+            Verification::Bad
+        );
+
+        let public_key = fs::read_to_string(key.with_extension("pub")).unwrap();
+        let allowed_signers = dir.path().join("allowed_signers");
+        fs::write(&allowed_signers, format!("test@example.com {public_key}")).unwrap();
+
+        let allowed_signers = allowed_signers.to_str().unwrap();
+        let verifier = SshVerifier::new(None, Some(allowed_signers));
+        assert_eq!(
+            verifier.verify(signature.as_bytes(), payload).unwrap(),
+            Verification::Good("test@example.com".to_string())
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::{collections::HashSet, error::Error};
+
+use clap::Args;
+use git2::ErrorCode;
+use gix::{prelude::ObjectIdExt, ObjectId};
+
+use crate::{
+    git::{Protection, Repo},
+    graph::Graph,
+    term::{
+        node::prelude::*,
+        render::{Render, Renderer},
+    },
+};
+
+#[derive(Args)]
+pub struct ForceOpts {
+    #[clap(
+        long,
+        help = "Rewrite commits even when they're already on the upstream"
+    )]
+    force: bool,
+}
+
+// Commits of HEAD that are not on the upstream, `None` when the branch has no upstream
+pub fn unpushed(repo: &Repo) -> Result<Option<HashSet<git2::Oid>>, Box<dyn Error>> {
+    let head = repo.head()?;
+
+    // Only branches have an upstream, a detached HEAD can be rewritten freely
+    if !head.0.is_branch() {
+        return Ok(None);
+    }
+
+    let local = head.target()?;
+    let upstream = match head.into_branch()?.upstream() {
+        Ok(upstream) => upstream.target()?,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let gix_repo = gix::open(repo.path())?;
+    let graph = Graph::ahead_behind(
+        &gix_repo,
+        ObjectId::try_from(local.as_bytes())?.attach(&gix_repo),
+        ObjectId::try_from(upstream.as_bytes())?.attach(&gix_repo),
+    )?;
+
+    Ok(Some(
+        graph
+            .ahead
+            .iter()
+            .map(|info| git2::Oid::from_bytes(info.id.as_bytes()))
+            .collect::<Result<_, _>>()?,
+    ))
+}
+
+// Rewriting a commit that's reachable from the upstream means force pushing later, this refuses
+// or warns depending on `src.protectPushed`
+pub fn check_rewrite(
+    repo: &Repo,
+    commit: git2::Oid,
+    opts: &ForceOpts,
+) -> Result<(), Box<dyn Error>> {
+    let protection = repo.config()?.rewrite.protect_pushed;
+
+    if opts.force || protection == Protection::Off {
+        return Ok(());
+    }
+
+    let Some(unpushed) = unpushed(repo)? else {
+        return Ok(());
+    };
+
+    if unpushed.contains(&commit) {
+        return Ok(());
+    }
+
+    match protection {
+        Protection::Warn => {
+            Renderer::default().renderln(
+                &text!("Rewriting commits that are already on the upstream")
+                    .with_status(Status::Warning),
+            )?;
+            Ok(())
+        }
+        _ => Err(
+            format!("{commit} is already on the upstream, use --force to rewrite it anyway").into(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    // A `main` branch with one commit on its upstream and one that's only local
+    fn init(dir: &TempDir, protection: &str) -> (Repo, git2::Oid, git2::Oid) {
+        let repo = git2::Repository::init_opts(
+            dir.path(),
+            git2::RepositoryInitOptions::new().initial_head("main"),
+        )
+        .unwrap();
+        let mut config = repo.config().unwrap();
+
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        config.set_str("src.protectPushed", protection).unwrap();
+        config.set_str("branch.main.remote", "origin").unwrap();
+        config
+            .set_str("branch.main.merge", "refs/heads/main")
+            .unwrap();
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+
+        let (pushed, local) = {
+            let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+            let tree = repo
+                .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+                .unwrap();
+            let pushed = repo
+                .commit(Some("HEAD"), &signature, &signature, "pushed", &tree, &[])
+                .unwrap();
+            let parent = repo.find_commit(pushed).unwrap();
+            let local = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    "local",
+                    &tree,
+                    &[&parent],
+                )
+                .unwrap();
+
+            (pushed, local)
+        };
+
+        repo.reference("refs/remotes/origin/main", pushed, false, "fetch")
+            .unwrap();
+
+        (Repo::from(repo), pushed, local)
+    }
+
+    #[test]
+    fn test_check_rewrite_refuse() {
+        let dir = TempDir::new().unwrap();
+        let (repo, pushed, local) = init(&dir, "refuse");
+
+        assert!(check_rewrite(&repo, pushed, &ForceOpts { force: false }).is_err());
+        assert!(check_rewrite(&repo, local, &ForceOpts { force: false }).is_ok());
+        assert!(check_rewrite(&repo, pushed, &ForceOpts { force: true }).is_ok());
+    }
+
+    #[test]
+    fn test_check_rewrite_warn_and_off() {
+        for protection in ["warn", "off"] {
+            let dir = TempDir::new().unwrap();
+            let (repo, pushed, local) = init(&dir, protection);
+
+            assert!(check_rewrite(&repo, pushed, &ForceOpts { force: false }).is_ok());
+            assert!(check_rewrite(&repo, local, &ForceOpts { force: false }).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_check_rewrite_without_upstream() {
+        let dir = TempDir::new().unwrap();
+        let (repo, pushed, _) = init(&dir, "refuse");
+
+        repo.find_branch("main")
+            .unwrap()
+            .0
+            .set_upstream(None)
+            .unwrap();
+
+        assert!(check_rewrite(&repo, pushed, &ForceOpts { force: false }).is_ok());
+    }
+}
@@ -9,11 +9,14 @@ use colored::Colorize;
 use git::Repo;
 use git2::{Repository, RepositoryOpenFlags};
 use resolve_path::PathResolveExt;
+use term::render::{self, ColorChoice, Format};
+use term::theme::{self, Theme};
 use tracing_subscriber::EnvFilter;
 
 mod cmd;
 mod git;
 mod graph;
+mod guard;
 mod progress;
 mod rebase;
 mod term;
@@ -31,16 +34,39 @@ struct Opts {
 
     #[arg(long = "generate", value_enum)]
     generator: Option<Shell>,
+
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        default_value = "text",
+        help = "Output format"
+    )]
+    format: Format,
+
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        default_value = "auto",
+        help = "When to use colors"
+    )]
+    color: ColorChoice,
+
+    #[clap(long, global = true, help = "Only use ASCII characters in the output")]
+    ascii: bool,
 }
 
 #[derive(Parser)]
 enum Cmd {
     Add(cmd::add::Opts),
+    Blame(cmd::blame::Opts),
     Fix(cmd::commit::Opts),
     Feat(cmd::commit::Opts),
     Refactor(cmd::commit::Opts),
     Chore(cmd::commit::Opts),
     Clone(cmd::clone::Opts),
+    Status(cmd::status::Opts),
     Commit(cmd::commit::Opts),
     Amend(cmd::amend::Opts),
     Push(cmd::push::Opts),
@@ -48,11 +74,28 @@ enum Cmd {
     Pull(cmd::pull::Opts),
     Sync(cmd::sync::Opts),
     List(cmd::list::Opts),
+    Log(cmd::log::Opts),
+    History(cmd::history::Opts),
     Diff(cmd::diff::Opts),
+    CherryPick(cmd::cherry_pick::Opts),
+    Revert(cmd::revert::Opts),
+    #[clap(about = "Undo the last operation recorded in the reflog")]
+    Undo(cmd::undo::Opts),
+    #[clap(about = "Redo the last undone operation")]
+    Redo(cmd::undo::Opts),
+    RestoreBackup(cmd::restore_backup::Opts),
+    Reset(cmd::reset::Opts),
+    Fixup(cmd::fixup::Opts),
+    Absorb(cmd::absorb::Opts),
+    Split(cmd::split::Opts),
+    Reword(cmd::reword::Opts),
+    Grep(cmd::grep::Opts),
     Stash(cmd::stash::Opts),
     Unstash(cmd::unstash::Opts),
     Branch(cmd::branch::Opts),
     Checkout(cmd::checkout::Opts),
+    Tag(cmd::tag::Opts),
+    Verify(cmd::verify::Opts),
 }
 
 fn open_gix(path: impl AsRef<Path>) -> Result<gix::Repository, gix::open::Error> {
@@ -86,12 +129,30 @@ fn open_repo(path: impl AsRef<Path>) -> Result<Repo, Box<dyn Error>> {
     )?))
 }
 
+fn load_theme(path: &Path) -> Result<Theme, theme::Error> {
+    let config = match Repository::open_ext(path, RepositoryOpenFlags::empty(), [path]) {
+        Ok(repo) => repo.config()?,
+        Err(_) => git2::Config::open_default()?,
+    };
+
+    Theme::from_config(&config)
+}
+
 fn main() {
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
     let opts = Opts::parse();
+    render::set_format(opts.format);
+    render::set_color(opts.color);
+    render::set_theme(
+        load_theme(&opts.dir).unwrap_or_else(|e| {
+            eprintln!("{}", format!("invalid theme: {e}").yellow());
+            Theme::default()
+        }),
+        opts.ascii,
+    );
 
     if let Some(generator) = opts.generator {
         let mut cmd = Opts::command();
@@ -102,12 +163,14 @@ fn main() {
 
     let app = || match opts.cmd {
         Some(Cmd::Clone(opts)) => cmd::clone::run(opts),
+        Some(Cmd::Status(status_opts)) => cmd::status::run(open_gix(&opts.dir)?, status_opts),
         cmd => match cmd {
             Some(cmd) => {
                 let repo = open_repo(&opts.dir)?;
 
                 match cmd {
                     Cmd::Add(opts) => cmd::add::run(repo, opts),
+                    Cmd::Blame(opts) => cmd::blame::run(repo, opts),
                     Cmd::Fix(opts) => cmd::commit::with_prefix("fix", repo, opts),
                     Cmd::Feat(opts) => cmd::commit::with_prefix("feat", repo, opts),
                     Cmd::Chore(opts) => cmd::commit::with_prefix("chore", repo, opts),
@@ -119,12 +182,27 @@ fn main() {
                     Cmd::Pull(opts) => cmd::pull::run(repo, opts),
                     Cmd::Sync(opts) => cmd::sync::run(repo, opts),
                     Cmd::List(opts) => cmd::list::run(repo, opts),
+                    Cmd::Log(opts) => cmd::log::run(repo, opts),
+                    Cmd::History(opts) => cmd::history::run(repo, opts),
                     Cmd::Diff(opts) => cmd::diff::run(repo, opts),
+                    Cmd::CherryPick(opts) => cmd::cherry_pick::run(repo, opts),
+                    Cmd::Revert(opts) => cmd::revert::run(repo, opts),
+                    Cmd::Undo(opts) => cmd::undo::run(repo, opts),
+                    Cmd::Redo(opts) => cmd::undo::redo(repo, opts),
+                    Cmd::RestoreBackup(opts) => cmd::restore_backup::run(repo, opts),
+                    Cmd::Reset(opts) => cmd::reset::run(repo, opts),
+                    Cmd::Fixup(opts) => cmd::fixup::run(repo, opts),
+                    Cmd::Absorb(opts) => cmd::absorb::run(repo, opts),
+                    Cmd::Split(opts) => cmd::split::run(repo, opts),
+                    Cmd::Reword(opts) => cmd::reword::run(repo, opts),
+                    Cmd::Grep(opts) => cmd::grep::run(repo, opts),
                     Cmd::Stash(opts) => cmd::stash::run(repo, opts),
                     Cmd::Unstash(opts) => cmd::unstash::run(repo, opts),
                     Cmd::Branch(opts) => cmd::branch::run(repo, opts),
                     Cmd::Checkout(opts) => cmd::checkout::run(repo, opts),
-                    Cmd::Clone(_) => unreachable!(),
+                    Cmd::Tag(opts) => cmd::tag::run(repo, opts),
+                    Cmd::Verify(opts) => cmd::verify::run(repo, opts),
+                    Cmd::Clone(_) | Cmd::Status(_) => unreachable!(),
                 }
             }
             None => match opts.branch {
@@ -138,6 +216,12 @@ fn main() {
     };
 
     if let Err(e) = app() {
-        eprintln!("{}", format!("⚠️ {e}").red());
+        match opts.format {
+            Format::Text => eprintln!(
+                "{}",
+                format!("{} {e}", render::theme().glyphs.conflict).red()
+            ),
+            Format::Json => eprintln!("{}", serde_json::json!({ "error": e.to_string() })),
+        }
     }
 }
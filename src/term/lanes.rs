@@ -0,0 +1,169 @@
+use super::theme::Glyphs;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell {
+    Empty,
+    Lane,
+    Commit,
+    Horizontal,
+    ForkLeft,
+    ForkRight,
+    JoinLeft,
+    JoinRight,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Row {
+    cells: Vec<Cell>,
+    span: Option<(usize, usize)>,
+}
+
+impl Row {
+    pub fn render(&self, glyphs: &Glyphs) -> String {
+        let mut output = String::new();
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            output.push_str(match cell {
+                Cell::Empty => " ",
+                Cell::Lane => &*glyphs.graph_lane,
+                Cell::Commit => &*glyphs.graph_commit,
+                Cell::Horizontal => &*glyphs.graph_horizontal,
+                Cell::ForkLeft => &*glyphs.graph_fork_left,
+                Cell::ForkRight => &*glyphs.graph_fork_right,
+                Cell::JoinLeft => &*glyphs.graph_join_left,
+                Cell::JoinRight => &*glyphs.graph_join_right,
+            });
+
+            output.push_str(match self.span {
+                Some((start, end)) if i >= start && i < end => &*glyphs.graph_horizontal,
+                _ => " ",
+            });
+        }
+
+        output
+    }
+}
+
+// Assigns every commit to a lane (column), the lane is then passed on to the first parent while
+// additional parents of merge commits get a lane of their own
+pub struct Lanes<T> {
+    lanes: Vec<Option<T>>,
+}
+
+impl<T> Default for Lanes<T> {
+    fn default() -> Self {
+        Self { lanes: vec![] }
+    }
+}
+
+impl<T: PartialEq + Clone> Lanes<T> {
+    fn allocate(&mut self, skip: usize) -> usize {
+        match (0..self.lanes.len()).find(|&i| i != skip && self.lanes[i].is_none()) {
+            Some(i) => i,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+
+    pub fn next(&mut self, id: &T, parents: &[T]) -> Row {
+        let active = self
+            .lanes
+            .iter()
+            .map(|lane| lane.is_some())
+            .collect::<Vec<_>>();
+        let waiting = (0..self.lanes.len())
+            .filter(|&i| self.lanes[i].as_ref() == Some(id))
+            .collect::<Vec<_>>();
+
+        // Commits that aren't a parent of anything we've seen yet start a new lane
+        let column = match waiting.first() {
+            Some(&i) => i,
+            None => self.allocate(usize::MAX),
+        };
+
+        let joins = waiting.into_iter().skip(1).collect::<Vec<_>>();
+
+        for &i in &joins {
+            self.lanes[i] = None;
+        }
+
+        self.lanes[column] = parents.first().cloned();
+
+        let mut forks = vec![];
+
+        for parent in parents.iter().skip(1) {
+            let i = self.allocate(column);
+            self.lanes[i] = Some(parent.clone());
+            forks.push(i);
+        }
+
+        let cells = (0..self.lanes.len())
+            .map(|i| match i {
+                i if i == column => Cell::Commit,
+                i if joins.contains(&i) && i < column => Cell::JoinLeft,
+                i if joins.contains(&i) => Cell::JoinRight,
+                i if forks.contains(&i) && i < column => Cell::ForkLeft,
+                i if forks.contains(&i) => Cell::ForkRight,
+                i if active.get(i).copied().unwrap_or_default() => Cell::Lane,
+                _ => Cell::Empty,
+            })
+            .collect::<Vec<_>>();
+
+        let edges = joins.iter().chain(forks.iter()).copied();
+        let span = edges
+            .clone()
+            .min()
+            .map(|min| (min.min(column), edges.max().unwrap_or(column).max(column)));
+
+        while self.lanes.last().is_some_and(Option::is_none) {
+            self.lanes.pop();
+        }
+
+        let cells = cells
+            .into_iter()
+            .enumerate()
+            .map(|(i, cell)| match span {
+                Some((start, end)) if cell == Cell::Empty && i > start && i < end => {
+                    Cell::Horizontal
+                }
+                _ => cell,
+            })
+            .collect();
+
+        Row { cells, span }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(lanes: &mut Lanes<char>, id: char, parents: &[char]) -> String {
+        lanes
+            .next(&id, parents)
+            .render(&Glyphs::ASCII)
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn test_linear() {
+        let mut lanes = Lanes::default();
+
+        assert_eq!(render(&mut lanes, 'c', &['b']), "*");
+        assert_eq!(render(&mut lanes, 'b', &['a']), "*");
+        assert_eq!(render(&mut lanes, 'a', &[]), "*");
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut lanes = Lanes::default();
+
+        assert_eq!(render(&mut lanes, 'm', &['b', 'c']), "*-\\");
+        assert_eq!(render(&mut lanes, 'b', &['a']), "* |");
+        assert_eq!(render(&mut lanes, 'c', &['a']), "| *");
+        assert_eq!(render(&mut lanes, 'a', &[]), "*-/");
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    borrow::Cow,
+    env,
+    io::{stdout, IsTerminal},
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const ELLIPSIS: &str = "...";
+
+pub fn terminal_width() -> Option<usize> {
+    // Output is not truncated when it's piped to another program
+    if !stdout().is_terminal() {
+        return None;
+    }
+
+    match crossterm::terminal::size() {
+        Ok((columns, _)) if columns > 0 => Some(columns as usize),
+        _ => env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok()),
+    }
+}
+
+pub fn display_width(text: &str) -> usize {
+    if text.contains('\x1b') {
+        return strip_ansi_escapes::strip_str(text).width();
+    }
+
+    text.width()
+}
+
+pub fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+
+    let Some(available) = width.checked_sub(ELLIPSIS.len()) else {
+        return Cow::Owned(ELLIPSIS[..width].to_string());
+    };
+
+    let mut used = 0;
+    let mut output = String::new();
+
+    for grapheme in text.graphemes(true) {
+        used += grapheme.width();
+
+        if used > available {
+            break;
+        }
+
+        output.push_str(grapheme);
+    }
+
+    output.push_str(ELLIPSIS);
+    Cow::Owned(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly 10", 10), "exactly 10");
+        assert_eq!(truncate("a bit too long", 10), "a bit t...");
+        assert_eq!(truncate("añadir soporte", 10), "añadir ...");
+        assert_eq!(truncate("日本語のコミット", 10), "日本語...");
+        assert_eq!(truncate("too long", 2), "..");
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("\x1b[33mabc\x1b[0m"), 3);
+    }
+}
@@ -1,18 +1,25 @@
 use std::{
+    env, fs, io,
+    path::Path,
+    process::Command,
     sync::mpsc::Receiver,
     thread::{self, JoinHandle},
     time::Instant,
 };
 
-use inquire::{error::InquireResult, ui::RenderConfig, Confirm};
+use chrono::{DateTime, Local};
+use inquire::{error::InquireResult, ui::RenderConfig, Confirm, Text};
 use progress::ProgressBar;
 
 use crate::git::{ProgressEvent, SidebandOp};
 
+pub mod lanes;
+pub mod layout;
 pub mod node;
 pub mod progress;
 pub mod render;
 pub mod select;
+pub mod theme;
 
 pub fn confirm(prompt: &str) -> InquireResult<bool> {
     let mut config = RenderConfig::default_colored();
@@ -24,6 +31,66 @@ pub fn confirm(prompt: &str) -> InquireResult<bool> {
         .prompt()
 }
 
+// Opens the file with `$GIT_EDITOR`, the configured editor, `$VISUAL` or `$EDITOR` (in that
+// order) and returns the content without comment lines
+pub fn edit(path: &Path, content: &str, configured: Option<&str>) -> io::Result<String> {
+    let editor = env::var("GIT_EDITOR")
+        .ok()
+        .or(configured.map(ToString::to_string))
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .unwrap_or_else(|| "vi".to_string());
+
+    fs::write(path, content)?;
+
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg(&editor)
+        .arg(path)
+        .status()?;
+
+    if !status.success() {
+        return Err(io::Error::other(format!("editor exited with {status}")));
+    }
+
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string())
+}
+
+pub fn input(prompt: &str, default: &str) -> InquireResult<String> {
+    let mut config = RenderConfig::default_colored();
+    config.prompt.fg = Some(inquire::ui::Color::LightCyan);
+
+    Text::new(prompt)
+        .with_default(default)
+        .with_render_config(config)
+        .prompt()
+}
+
+pub fn relative_time(time: DateTime<Local>) -> String {
+    let seconds = (Local::now() - time).num_seconds().max(0);
+    let (amount, unit) = match seconds {
+        0..60 => return "just now".to_string(),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        86400..604800 => (seconds / 86400, "day"),
+        604800..2592000 => (seconds / 604800, "week"),
+        2592000..31536000 => (seconds / 2592000, "month"),
+        _ => (seconds / 31536000, "year"),
+    };
+
+    match amount {
+        1 => format!("1 {unit} ago"),
+        _ => format!("{amount} {unit}s ago"),
+    }
+}
+
 pub fn setup_progress_bar(rx: Receiver<ProgressEvent>) -> JoinHandle<()> {
     thread::spawn(move || {
         let mut now = Instant::now();
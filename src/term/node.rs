@@ -54,6 +54,12 @@ macro_rules! continued {
     };
 }
 
+macro_rules! right_aligned {
+    ($content: expr) => {
+        Node::RightAligned(Box::new($content))
+    };
+}
+
 macro_rules! spacer {
     () => {
         Node::spacer()
@@ -68,12 +74,13 @@ pub(crate) use dimmed;
 pub(crate) use icon;
 pub(crate) use label;
 pub(crate) use multi_line;
+pub(crate) use right_aligned;
 pub(crate) use spacer;
 pub(crate) use text;
 
 pub mod prelude {
     pub(crate) use super::{
-        block, breadcrumb, continued, dimmed, icon, label, multi_line, spacer, text,
+        block, breadcrumb, continued, dimmed, icon, label, multi_line, right_aligned, spacer, text,
     };
     pub use super::{message_with_icon, Attribute, Icon, Indicator, Node, Status};
 }
@@ -97,6 +104,27 @@ pub enum Attribute {
 }
 
 impl Attribute {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attribute::Commit(_) => "commit",
+            Attribute::CommitShort(_) => "commit_short",
+            Attribute::Tag(_) => "tag",
+            Attribute::Branch(_) => "branch",
+            Attribute::Remote(_) => "remote",
+            Attribute::Operation(_) => "operation",
+        }
+    }
+
+    pub fn value(&self) -> String {
+        match self {
+            Attribute::Commit(oid) | Attribute::CommitShort(oid) => oid.to_string(),
+            Attribute::Tag(name)
+            | Attribute::Branch(name)
+            | Attribute::Remote(name)
+            | Attribute::Operation(name) => name.to_string(),
+        }
+    }
+
     pub fn from_object(object: &gix::Object) -> Result<Attribute, Box<dyn Error>> {
         match object.kind {
             gix::objs::Kind::Tree => todo!(),
@@ -114,12 +142,35 @@ pub enum Status {
     Success,
 }
 
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Error => "error",
+            Status::Warning => "warning",
+            Status::Success => "success",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Icon {
     ArrowUp,
     ArrowDown,
     Lock,
     Check,
+    Cross,
+}
+
+impl Icon {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Icon::ArrowUp => "arrow_up",
+            Icon::ArrowDown => "arrow_down",
+            Icon::Lock => "lock",
+            Icon::Check => "check",
+            Icon::Cross => "cross",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -132,6 +183,19 @@ pub enum Indicator {
     Deleted,
 }
 
+impl Indicator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Indicator::Unknown => "unknown",
+            Indicator::New => "new",
+            Indicator::Conflict => "conflict",
+            Indicator::Modified => "modified",
+            Indicator::Renamed => "renamed",
+            Indicator::Deleted => "deleted",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Node {
     Empty,
@@ -144,6 +208,8 @@ pub enum Node {
     Continued(Box<Node>),
     Breadcrumb(Vec<Node>),
     Text(Cow<'static, str>),
+    Truncated(Cow<'static, str>, usize),
+    RightAligned(Box<Node>),
     Attribute(Attribute),
     Status(Status, Box<Node>),
     Column(Box<Node>, Box<Node>),
@@ -166,14 +232,14 @@ impl Node {
         )
     }
 
+    // Truncated to `cap` columns, or less when the line doesn't fit in the terminal
     pub fn text_capped(text: impl ToString, cap: usize) -> Node {
-        let text = text.to_string();
+        Node::Truncated(text.to_string().into(), cap)
+    }
 
-        if text.len() > cap {
-            Node::Text(format!("{}...", &text[..cap - 3]).into())
-        } else {
-            Node::Text(text.into())
-        }
+    // Takes up the rest of the line and is truncated to the terminal width
+    pub fn text_fill(text: impl ToString) -> Node {
+        Node::Truncated(text.to_string().into(), usize::MAX)
     }
 
     pub fn with_status(self, status: Status) -> Self {
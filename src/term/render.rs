@@ -1,17 +1,85 @@
+use std::env;
 use std::fmt::{self, Arguments};
-use std::io::Write;
+use std::io::{IsTerminal, Write};
+use std::sync::OnceLock;
 
+use clap::ValueEnum;
 use colored::{Color, Colorize};
+use serde_json::{json, Value};
 
 use crate::term::node::Status;
 
+use super::layout;
 use super::node::{Attribute, Icon, Indicator, Node};
+use super::theme::{Glyphs, Theme};
 
-const HEADER: Color = Color::TrueColor {
-    r: 225,
-    g: 190,
-    b: 120,
-};
+static FORMAT: OnceLock<Format> = OnceLock::new();
+static COLOR: OnceLock<ColorChoice> = OnceLock::new();
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+fn is_dumb_terminal() -> bool {
+    env::var("TERM").is_ok_and(|term| term == "dumb")
+}
+
+pub fn set_color(choice: ColorChoice) {
+    let _ = COLOR.set(choice);
+
+    match choice {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto if is_dumb_terminal() => colored::control::set_override(false),
+        // Colored already honours NO_COLOR, CLICOLOR(_FORCE) and non-TTY output
+        ColorChoice::Auto => {}
+    }
+}
+
+pub fn enable_pager_color() {
+    let auto = COLOR.get().copied().unwrap_or_default() == ColorChoice::Auto;
+
+    // The pager writes to the terminal, but the content is rendered to a buffer first
+    if auto
+        && env::var_os("NO_COLOR").is_none()
+        && !is_dumb_terminal()
+        && std::io::stdout().is_terminal()
+    {
+        colored::control::set_override(true);
+    }
+}
+
+pub fn set_theme(mut theme: Theme, ascii: bool) {
+    if ascii || is_dumb_terminal() {
+        theme.glyphs = Glyphs::ASCII;
+    }
+
+    let _ = THEME.set(theme);
+}
+
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+pub fn set_format(format: Format) {
+    let _ = FORMAT.set(format);
+}
+
+pub fn format() -> Format {
+    FORMAT.get().copied().unwrap_or_default()
+}
 
 pub struct WriteFmt<T: Write>(pub T);
 
@@ -32,6 +100,10 @@ pub trait Render {
 pub struct TermRenderer<W: fmt::Write> {
     writer: W,
     color: Option<Color>,
+    theme: &'static Theme,
+    width: Option<usize>,
+    column: usize,
+    reserved: usize,
 }
 
 impl<W: fmt::Write> TermRenderer<W> {
@@ -39,6 +111,10 @@ impl<W: fmt::Write> TermRenderer<W> {
         Self {
             writer: w,
             color: None,
+            theme: theme(),
+            width: layout::terminal_width(),
+            column: 0,
+            reserved: 0,
         }
     }
 
@@ -51,12 +127,34 @@ impl<W: fmt::Write> TermRenderer<W> {
         Ok(())
     }
 
+    // Renders a node without a width limit to find out how much space it takes
+    fn measure(&self, node: &Node) -> usize {
+        let mut renderer = TermRenderer {
+            writer: String::new(),
+            color: None,
+            theme: self.theme,
+            width: None,
+            column: 0,
+            reserved: 0,
+        };
+
+        match renderer.render(node) {
+            Ok(()) => layout::display_width(&renderer.writer),
+            Err(_) => 0,
+        }
+    }
+
     fn write_fmt(&mut self, args: Arguments<'_>) -> fmt::Result {
+        let text = fmt::format(args);
+
+        self.column = match text.rsplit_once('\n') {
+            Some((_, last)) => layout::display_width(last),
+            None => self.column + layout::display_width(&text),
+        };
+
         match self.color {
-            Some(color) => self
-                .writer
-                .write_str(&fmt::format(args).color(color).to_string()),
-            None => self.writer.write_fmt(args),
+            Some(color) => self.writer.write_str(&text.color(color).to_string()),
+            None => self.writer.write_str(&text),
         }
     }
 }
@@ -75,24 +173,58 @@ macro_rules! write {
 
 impl<W: fmt::Write> Render for TermRenderer<W> {
     fn render(&mut self, node: &Node) -> fmt::Result {
+        let theme = self.theme;
+        let Theme { colors, glyphs } = theme;
+
         match node {
-            Node::Dimmed(node) => self.render_with(node, Color::BrightBlack),
+            Node::Dimmed(node) => self.render_with(node, colors.dimmed),
             Node::Text(text) => write!(self, "{text}"),
             Node::Block(children) => {
+                let state = self.reserved;
+                let reserved = children
+                    .iter()
+                    .filter(|node| matches!(node, Node::RightAligned(_)))
+                    .map(|node| self.measure(node) + 1)
+                    .sum::<usize>();
+                self.reserved += reserved;
+
                 for node in children {
                     self.render(node)?;
                 }
 
+                self.reserved = state;
                 Ok(())
             }
+            Node::Truncated(text, cap) => {
+                let available = match self.width {
+                    Some(width) => width.saturating_sub(self.column + self.reserved),
+                    None => usize::MAX,
+                };
+
+                write!(self, "{}", layout::truncate(text, available.min(*cap)))
+            }
+            Node::RightAligned(node) => {
+                let width = self.measure(node);
+                self.reserved = self.reserved.saturating_sub(width + 1);
+
+                let padding = match self.width {
+                    Some(total) => total
+                        .saturating_sub(self.column + width + self.reserved)
+                        .max(1),
+                    None => 1,
+                };
+
+                write!(self, "{:padding$}", "")?;
+                self.render(node)
+            }
             Node::Continued(node) => {
-                write!(self, "↪ ")?;
+                write!(self, "{}", glyphs.continued)?;
                 self.render(node)
             }
             Node::Breadcrumb(children) => {
                 for (i, node) in children.iter().enumerate() {
                     if i > 0 {
-                        write!(self, " › ")?;
+                        write!(self, "{}", glyphs.separator)?;
                     }
 
                     self.render(node)?;
@@ -108,16 +240,40 @@ impl<W: fmt::Write> Render for TermRenderer<W> {
                         .chars()
                         .take(7)
                         .collect::<String>()
-                        .yellow()
+                        .color(colors.commit)
                 ),
-                Attribute::Commit(oid) => write!(self.writer, "{}", oid.to_string().yellow()),
-                Attribute::Tag(name) => write!(self, "{}", format!("#{name}").blue()),
-                Attribute::Branch(name) => write!(self, "{}", format!(" {name}").blue()),
-                Attribute::Remote(name) => write!(self, "{}", format!("⬡ {name}").cyan()),
-                Attribute::Operation(name) => write!(self, "{}", format!("↻ {name}").cyan()),
+                Attribute::Commit(oid) => {
+                    write!(self, "{}", oid.to_string().color(colors.commit))
+                }
+                Attribute::Tag(name) => write!(self, "{}", format!("#{name}").color(colors.tag)),
+                Attribute::Branch(name) => {
+                    write!(
+                        self,
+                        "{}",
+                        format!("{}{name}", glyphs.branch).color(colors.branch)
+                    )
+                }
+                Attribute::Remote(name) => {
+                    write!(
+                        self,
+                        "{}",
+                        format!("{}{name}", glyphs.remote).color(colors.remote)
+                    )
+                }
+                Attribute::Operation(name) => {
+                    write!(
+                        self,
+                        "{}",
+                        format!("{}{name}", glyphs.operation).color(colors.operation)
+                    )
+                }
             },
             Node::Group(heading, count, node) => {
-                write!(self, "\n{}", format!("{heading}").color(HEADER).bold())?;
+                write!(
+                    self,
+                    "\n{}",
+                    format!("{heading}").color(colors.header).bold()
+                )?;
 
                 if let Some(count) = count {
                     write!(self, " {}", format!("({})", count).dimmed())?;
@@ -138,23 +294,24 @@ impl<W: fmt::Write> Render for TermRenderer<W> {
                 Ok(())
             }
             Node::Icon(icon) => match icon {
-                Icon::ArrowUp => write!(self.writer, "↑"),
-                Icon::ArrowDown => write!(self, "↓"),
-                Icon::Lock => write!(self, "⚿"),
-                Icon::Check => write!(self, "✓"),
+                Icon::ArrowUp => write!(self, "{}", glyphs.arrow_up),
+                Icon::ArrowDown => write!(self, "{}", glyphs.arrow_down),
+                Icon::Lock => write!(self, "{}", glyphs.lock),
+                Icon::Check => write!(self, "{}", glyphs.check),
+                Icon::Cross => write!(self, "{}", glyphs.cross),
             },
             Node::Indicator(indicator) => match indicator {
-                Indicator::Unknown => write!(self, "{}", "?".bright_black()),
-                Indicator::Conflict => write!(self, "{}", "⚠".yellow()),
-                Indicator::New => write!(self, "{}", "✚".green()),
-                Indicator::Modified => write!(self, "{}", "~".yellow()),
-                Indicator::Renamed => write!(self, "{}", "➜".yellow()),
-                Indicator::Deleted => write!(self, "{}", "✖".red()),
+                Indicator::Unknown => write!(self, "{}", glyphs.unknown.color(colors.unknown)),
+                Indicator::Conflict => write!(self, "{}", glyphs.conflict.color(colors.conflict)),
+                Indicator::New => write!(self, "{}", glyphs.new.color(colors.new)),
+                Indicator::Modified => write!(self, "{}", glyphs.modified.color(colors.modified)),
+                Indicator::Renamed => write!(self, "{}", glyphs.renamed.color(colors.renamed)),
+                Indicator::Deleted => write!(self, "{}", glyphs.deleted.color(colors.deleted)),
             },
             Node::Status(status, node) => match status {
-                Status::Error => self.render_with(node, Color::Red),
-                Status::Warning => self.render_with(node, Color::Yellow),
-                Status::Success => self.render_with(node, Color::Green),
+                Status::Error => self.render_with(node, colors.error),
+                Status::Warning => self.render_with(node, colors.warning),
+                Status::Success => self.render_with(node, colors.success),
             },
             Node::Label(node) => {
                 write!(self, "{}", "(".dimmed())?;
@@ -170,3 +327,122 @@ impl<W: fmt::Write> Render for TermRenderer<W> {
         }
     }
 }
+
+fn to_json(node: &Node) -> Value {
+    match node {
+        Node::Empty => Value::Null,
+        Node::Dimmed(node) | Node::Continued(node) | Node::RightAligned(node) => to_json(node),
+        Node::Text(text) | Node::Truncated(text, _) => json!({ "type": "text", "text": text }),
+        Node::Icon(icon) => json!({ "type": "icon", "name": icon.name() }),
+        Node::Indicator(indicator) => json!({ "type": "indicator", "kind": indicator.name() }),
+        Node::Attribute(attr) => {
+            json!({ "type": "attribute", "kind": attr.name(), "value": attr.value() })
+        }
+        Node::Label(node) => json!({ "type": "label", "child": to_json(node) }),
+        Node::Status(status, node) => {
+            json!({ "type": "status", "status": status.name(), "child": to_json(node) })
+        }
+        Node::Column(left, right) => {
+            json!({ "type": "column", "key": to_json(left), "value": to_json(right) })
+        }
+        Node::Block(children) => json!({ "type": "block", "children": to_json_list(children) }),
+        Node::Breadcrumb(children) => {
+            json!({ "type": "breadcrumb", "children": to_json_list(children) })
+        }
+        Node::MultiLine(children) => {
+            json!({ "type": "lines", "children": to_json_list(children) })
+        }
+        Node::Group(heading, count, node) => json!({
+            "type": "group",
+            "heading": heading,
+            "count": count,
+            "child": to_json(node),
+        }),
+    }
+}
+
+fn to_json_list(children: &[Node]) -> Vec<Value> {
+    children
+        .iter()
+        .filter(|node| !matches!(node, Node::Text(text) if text.trim().is_empty()))
+        .map(to_json)
+        .filter(|value| !value.is_null())
+        .collect()
+}
+
+pub struct JsonRenderer<W: fmt::Write> {
+    writer: W,
+    pending: Vec<Value>,
+}
+
+impl<W: fmt::Write> JsonRenderer<W> {
+    pub fn new(w: W) -> Self {
+        Self {
+            writer: w,
+            pending: vec![],
+        }
+    }
+
+    fn flush(&mut self) -> fmt::Result {
+        let value = match self.pending.len() {
+            0 => return Ok(()),
+            1 => self.pending.remove(0),
+            _ => json!({ "type": "block", "children": std::mem::take(&mut self.pending) }),
+        };
+
+        writeln!(self.writer, "{value}")
+    }
+}
+
+impl<W: fmt::Write> Render for JsonRenderer<W> {
+    fn render(&mut self, node: &Node) -> fmt::Result {
+        match node {
+            Node::Text(text) if text == "\n" => self.flush(),
+            Node::Text(text) if text.trim().is_empty() => Ok(()),
+            node => {
+                let value = to_json(node);
+
+                if !value.is_null() {
+                    self.pending.push(value);
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<W: fmt::Write> Drop for JsonRenderer<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+pub enum Renderer<W: fmt::Write> {
+    Text(TermRenderer<W>),
+    Json(JsonRenderer<W>),
+}
+
+impl<W: fmt::Write> Renderer<W> {
+    pub fn new(w: W) -> Self {
+        match format() {
+            Format::Text => Renderer::Text(TermRenderer::new(w)),
+            Format::Json => Renderer::Json(JsonRenderer::new(w)),
+        }
+    }
+}
+
+impl Default for Renderer<WriteFmt<std::io::Stdout>> {
+    fn default() -> Self {
+        Self::new(WriteFmt(std::io::stdout()))
+    }
+}
+
+impl<W: fmt::Write> Render for Renderer<W> {
+    fn render(&mut self, node: &Node) -> fmt::Result {
+        match self {
+            Renderer::Text(renderer) => renderer.render(node),
+            Renderer::Json(renderer) => renderer.render(node),
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use std::borrow::Cow;
+
+use colored::Color;
+
+use crate::git::Optional;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("git error: {0}")]
+    Git(#[from] git2::Error),
+    #[error("invalid utf8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("unknown theme: {0}")]
+    UnknownPreset(String),
+    #[error("unknown theme key: {0}")]
+    UnknownKey(String),
+    #[error("invalid color for {0}: {1}")]
+    InvalidColor(String, String),
+}
+
+pub struct Glyphs {
+    pub continued: Cow<'static, str>,
+    pub separator: Cow<'static, str>,
+    pub branch: Cow<'static, str>,
+    pub remote: Cow<'static, str>,
+    pub operation: Cow<'static, str>,
+    pub arrow_up: Cow<'static, str>,
+    pub arrow_down: Cow<'static, str>,
+    pub lock: Cow<'static, str>,
+    pub check: Cow<'static, str>,
+    pub cross: Cow<'static, str>,
+    pub unknown: Cow<'static, str>,
+    pub conflict: Cow<'static, str>,
+    pub new: Cow<'static, str>,
+    pub modified: Cow<'static, str>,
+    pub renamed: Cow<'static, str>,
+    pub deleted: Cow<'static, str>,
+    pub graph_commit: Cow<'static, str>,
+    pub graph_lane: Cow<'static, str>,
+    pub graph_horizontal: Cow<'static, str>,
+    pub graph_fork_left: Cow<'static, str>,
+    pub graph_fork_right: Cow<'static, str>,
+    pub graph_join_left: Cow<'static, str>,
+    pub graph_join_right: Cow<'static, str>,
+}
+
+impl Glyphs {
+    pub const UNICODE: Glyphs = Glyphs {
+        continued: Cow::Borrowed("↪ "),
+        separator: Cow::Borrowed(" › "),
+        branch: Cow::Borrowed(" "),
+        remote: Cow::Borrowed("⬡ "),
+        operation: Cow::Borrowed("↻ "),
+        arrow_up: Cow::Borrowed("↑"),
+        arrow_down: Cow::Borrowed("↓"),
+        lock: Cow::Borrowed("⚿"),
+        check: Cow::Borrowed("✓"),
+        cross: Cow::Borrowed("✗"),
+        unknown: Cow::Borrowed("?"),
+        conflict: Cow::Borrowed("⚠"),
+        new: Cow::Borrowed("✚"),
+        modified: Cow::Borrowed("~"),
+        renamed: Cow::Borrowed("➜"),
+        deleted: Cow::Borrowed("✖"),
+        graph_commit: Cow::Borrowed("●"),
+        graph_lane: Cow::Borrowed("│"),
+        graph_horizontal: Cow::Borrowed("─"),
+        graph_fork_left: Cow::Borrowed("╭"),
+        graph_fork_right: Cow::Borrowed("╮"),
+        graph_join_left: Cow::Borrowed("╰"),
+        graph_join_right: Cow::Borrowed("╯"),
+    };
+
+    pub const ASCII: Glyphs = Glyphs {
+        continued: Cow::Borrowed("-> "),
+        separator: Cow::Borrowed(" > "),
+        branch: Cow::Borrowed(""),
+        remote: Cow::Borrowed(""),
+        operation: Cow::Borrowed(""),
+        arrow_up: Cow::Borrowed("^"),
+        arrow_down: Cow::Borrowed("v"),
+        lock: Cow::Borrowed("S"),
+        check: Cow::Borrowed("ok"),
+        cross: Cow::Borrowed("x"),
+        unknown: Cow::Borrowed("?"),
+        conflict: Cow::Borrowed("!"),
+        new: Cow::Borrowed("+"),
+        modified: Cow::Borrowed("~"),
+        renamed: Cow::Borrowed(">"),
+        deleted: Cow::Borrowed("-"),
+        graph_commit: Cow::Borrowed("*"),
+        graph_lane: Cow::Borrowed("|"),
+        graph_horizontal: Cow::Borrowed("-"),
+        graph_fork_left: Cow::Borrowed("/"),
+        graph_fork_right: Cow::Borrowed("\\"),
+        graph_join_left: Cow::Borrowed("\\"),
+        graph_join_right: Cow::Borrowed("/"),
+    };
+
+    // Requires a patched font, see: https://www.nerdfonts.com/cheat-sheet
+    pub const NERD: Glyphs = Glyphs {
+        continued: Cow::Borrowed("\u{f432} "),
+        separator: Cow::Borrowed(" \u{e0b1} "),
+        branch: Cow::Borrowed("\u{e0a0} "),
+        remote: Cow::Borrowed("\u{f0c2} "),
+        operation: Cow::Borrowed("\u{f021} "),
+        arrow_up: Cow::Borrowed("\u{f062}"),
+        arrow_down: Cow::Borrowed("\u{f063}"),
+        lock: Cow::Borrowed("\u{f023}"),
+        check: Cow::Borrowed("\u{f00c}"),
+        cross: Cow::Borrowed("\u{f00d}"),
+        unknown: Cow::Borrowed("\u{f128}"),
+        conflict: Cow::Borrowed("\u{f071}"),
+        new: Cow::Borrowed("\u{f067}"),
+        modified: Cow::Borrowed("\u{f040}"),
+        renamed: Cow::Borrowed("\u{f061}"),
+        deleted: Cow::Borrowed("\u{f068}"),
+        graph_commit: Cow::Borrowed("●"),
+        graph_lane: Cow::Borrowed("│"),
+        graph_horizontal: Cow::Borrowed("─"),
+        graph_fork_left: Cow::Borrowed("╭"),
+        graph_fork_right: Cow::Borrowed("╮"),
+        graph_join_left: Cow::Borrowed("╰"),
+        graph_join_right: Cow::Borrowed("╯"),
+    };
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Cow<'static, str>> {
+        Some(match name {
+            "continued" => &mut self.continued,
+            "separator" => &mut self.separator,
+            "branch" => &mut self.branch,
+            "remote" => &mut self.remote,
+            "operation" => &mut self.operation,
+            "arrowup" => &mut self.arrow_up,
+            "arrowdown" => &mut self.arrow_down,
+            "lock" => &mut self.lock,
+            "check" => &mut self.check,
+            "cross" => &mut self.cross,
+            "unknown" => &mut self.unknown,
+            "conflict" => &mut self.conflict,
+            "new" => &mut self.new,
+            "modified" => &mut self.modified,
+            "renamed" => &mut self.renamed,
+            "deleted" => &mut self.deleted,
+            "graphcommit" => &mut self.graph_commit,
+            "graphlane" => &mut self.graph_lane,
+            "graphhorizontal" => &mut self.graph_horizontal,
+            "graphforkleft" => &mut self.graph_fork_left,
+            "graphforkright" => &mut self.graph_fork_right,
+            "graphjoinleft" => &mut self.graph_join_left,
+            "graphjoinright" => &mut self.graph_join_right,
+            _ => return None,
+        })
+    }
+}
+
+pub struct Colors {
+    pub header: Color,
+    pub dimmed: Color,
+    pub commit: Color,
+    pub tag: Color,
+    pub branch: Color,
+    pub remote: Color,
+    pub operation: Color,
+    pub unknown: Color,
+    pub conflict: Color,
+    pub new: Color,
+    pub modified: Color,
+    pub renamed: Color,
+    pub deleted: Color,
+    pub error: Color,
+    pub warning: Color,
+    pub success: Color,
+}
+
+impl Colors {
+    pub const DARK: Colors = Colors {
+        header: Color::TrueColor {
+            r: 225,
+            g: 190,
+            b: 120,
+        },
+        dimmed: Color::BrightBlack,
+        commit: Color::Yellow,
+        tag: Color::Blue,
+        branch: Color::Blue,
+        remote: Color::Cyan,
+        operation: Color::Cyan,
+        unknown: Color::BrightBlack,
+        conflict: Color::Yellow,
+        new: Color::Green,
+        modified: Color::Yellow,
+        renamed: Color::Yellow,
+        deleted: Color::Red,
+        error: Color::Red,
+        warning: Color::Yellow,
+        success: Color::Green,
+    };
+
+    // Yellow and cyan are hard to read on a light background
+    pub const LIGHT: Colors = Colors {
+        header: Color::TrueColor {
+            r: 140,
+            g: 90,
+            b: 10,
+        },
+        dimmed: Color::BrightBlack,
+        commit: Color::Magenta,
+        tag: Color::Blue,
+        branch: Color::Blue,
+        remote: Color::TrueColor {
+            r: 0,
+            g: 120,
+            b: 130,
+        },
+        operation: Color::TrueColor {
+            r: 0,
+            g: 120,
+            b: 130,
+        },
+        unknown: Color::BrightBlack,
+        conflict: Color::Magenta,
+        new: Color::Green,
+        modified: Color::Magenta,
+        renamed: Color::Magenta,
+        deleted: Color::Red,
+        error: Color::Red,
+        warning: Color::Magenta,
+        success: Color::Green,
+    };
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "header" => &mut self.header,
+            "dimmed" => &mut self.dimmed,
+            "commit" => &mut self.commit,
+            "tag" => &mut self.tag,
+            "branch" => &mut self.branch,
+            "remote" => &mut self.remote,
+            "operation" => &mut self.operation,
+            "unknown" => &mut self.unknown,
+            "conflict" => &mut self.conflict,
+            "new" => &mut self.new,
+            "modified" => &mut self.modified,
+            "renamed" => &mut self.renamed,
+            "deleted" => &mut self.deleted,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "success" => &mut self.success,
+            _ => return None,
+        })
+    }
+}
+
+pub struct Theme {
+    pub colors: Colors,
+    pub glyphs: Glyphs,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            colors: Colors::DARK,
+            glyphs: Glyphs::UNICODE,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            colors: Colors::LIGHT,
+            glyphs: Glyphs::UNICODE,
+        }
+    }
+
+    pub fn nerd() -> Self {
+        Self {
+            colors: Colors::DARK,
+            glyphs: Glyphs::NERD,
+        }
+    }
+
+    pub fn preset(name: &str) -> Result<Self, Error> {
+        match name {
+            "dark" => Ok(Self::dark()),
+            "light" => Ok(Self::light()),
+            "nerd" => Ok(Self::nerd()),
+            name => Err(Error::UnknownPreset(name.to_string())),
+        }
+    }
+
+    // Reads the preset from `src.theme` and overrides from `src.theme.color.<name>` and
+    // `src.theme.glyph.<name>`
+    pub fn from_config(config: &git2::Config) -> Result<Self, Error> {
+        let mut theme = match config.get_string("src.theme").optional()? {
+            Some(name) => Self::preset(&name)?,
+            None => Self::default(),
+        };
+
+        let mut entries = config.entries(Some("src.theme.*"))?;
+
+        while let Some(entry) = entries.next() {
+            let entry = entry?;
+            let name = std::str::from_utf8(entry.name_bytes())?;
+            let value = std::str::from_utf8(entry.value_bytes())?;
+            let components = name.split('.').collect::<Vec<_>>();
+
+            match components[2..] {
+                // The preset itself (`src.theme`) also matches
+                [] => {}
+                ["color", key] => {
+                    let color = theme
+                        .colors
+                        .get_mut(key)
+                        .ok_or_else(|| Error::UnknownKey(name.to_string()))?;
+                    *color = parse_color(value)
+                        .ok_or_else(|| Error::InvalidColor(name.to_string(), value.to_string()))?;
+                }
+                ["glyph", key] => {
+                    let glyph = theme
+                        .glyphs
+                        .get_mut(key)
+                        .ok_or_else(|| Error::UnknownKey(name.to_string()))?;
+                    *glyph = Cow::Owned(value.to_string());
+                }
+                _ => return Err(Error::UnknownKey(name.to_string())),
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let [r, g, b] = <[u8; 3]>::try_from(hex::decode(hex).ok()?).ok()?;
+        return Some(Color::TrueColor { r, g, b });
+    }
+
+    value.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("Blue"), Some(Color::Blue));
+        assert_eq!(parse_color("bright black"), Some(Color::BrightBlack));
+        assert_eq!(
+            parse_color("#e1be78"),
+            Some(Color::TrueColor {
+                r: 225,
+                g: 190,
+                b: 120
+            })
+        );
+        assert_eq!(parse_color("#e1be"), None);
+        assert_eq!(parse_color("mauve"), None);
+    }
+}